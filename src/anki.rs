@@ -0,0 +1,126 @@
+use crate::headings::chapter_heading_depth;
+use crate::node::Node;
+use crate::topic::Topic;
+use std::{cell::RefCell, fs::File, io::Write, path::Path, rc::Rc};
+
+/// Fields of each note, in the order they are written
+const FIELDS: [&str; 4] = ["Label", "Main", "Aka", "Proofs"];
+
+/// Check if a node is exported as a note
+fn is_note(data: &Topic) -> bool {
+	matches!(data.env.as_str(), "def" | "thm" | "lem")
+}
+
+/// Convert TeX math delimiters to those understood by Anki, i.e.
+/// `$$...$$` to `\[...\]` and `$...$` to `\(...\)`; escaped dollar signs
+/// are left as they are
+fn anki_math(s: &str) -> String {
+	let mut out = String::new();
+	let mut display = false;
+	let mut inline = false;
+	let mut chars = s.chars().peekable();
+	while let Some(c) = chars.next() {
+		match c {
+			'\\' => {
+				out.push(c);
+				if let Some(&next) = chars.peek() {
+					out.push(next);
+					chars.next();
+				}
+			}
+			'$' if chars.peek() == Some(&'$') && !inline => {
+				chars.next();
+				out.push_str(if display { "\\]" } else { "\\[" });
+				display = !display;
+			}
+			'$' if !display => {
+				out.push_str(if inline { "\\)" } else { "\\(" });
+				inline = !inline;
+			}
+			_ => out.push(c),
+		}
+	}
+	out
+}
+
+/// Format text as the HTML content of a field; tabs would end the field,
+/// so they are replaced with spaces
+fn anki_field(s: &str) -> String {
+	anki_math(s.trim())
+		.replace("&", "&amp;")
+		.replace("<", "&lt;")
+		.replace(">", "&gt;")
+		.replace("\t", " ")
+		.replace("\r\n", "<br>")
+		.replace("\n", "<br>")
+}
+
+/// Format a heading title as a tag; tags cannot contain spaces
+fn anki_tag(s: &str) -> String {
+	s.split_whitespace().collect::<Vec<&str>>().join("_")
+}
+
+/// Write a tab-separated file that can be imported into Anki, with one
+/// note per definition, theorem, and lemma in the order they appear in
+/// the document; notes are tagged with the title of the generated
+/// chapter (or top-level heading) they appear under, if any
+pub fn write_anki(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>],
+	max_heading_depth: usize,
+) {
+	let path = Path::new("../output/main.anki.txt");
+	let mut file =
+		File::create(path).expect("could not create .anki.txt file");
+	file
+		.write_all(b"#separator:tab\n#html:true\n#columns:")
+		.expect("");
+	file.write_all(FIELDS.join("\t").as_bytes()).expect("");
+	file.write_all(b"\tTags\n").expect("");
+	file
+		.write_all(
+			format!("#tags column:{}\n", FIELDS.len() + 1).as_bytes(),
+		)
+		.expect("");
+
+	let depth = chapter_heading_depth(max_heading_depth);
+	let mut chapter = String::new();
+	let mut notes = 0;
+	for n in sorted_nodes.iter().rev() {
+		let node = n.borrow();
+		let data = node.data();
+
+		// Keep track of the chapter each node appears under; a heading
+		// above the chapter level ends the chapter
+		for (i, title) in data.heading_titles.iter().enumerate() {
+			let d = data.heading_depth_start + i;
+			if title.is_empty() || d > depth {
+				continue;
+			}
+			chapter = if d == depth {
+				title.clone()
+			} else {
+				"".to_string()
+			};
+		}
+		if !is_note(data) {
+			continue;
+		}
+
+		let row = [
+			anki_field(&data.label),
+			anki_field(&data.main),
+			anki_field(&data.aka.join("; ")),
+			data
+				.pfs
+				.iter()
+				.map(|p| anki_field(p))
+				.collect::<Vec<String>>()
+				.join("<hr>"),
+			anki_tag(&chapter),
+		];
+		file.write_all(row.join("\t").as_bytes()).expect("");
+		file.write_all(b"\n").expect("");
+		notes += 1;
+	}
+	println!("Wrote {} notes to {}", notes, path.display());
+}
@@ -0,0 +1,343 @@
+use crate::graph::clean_path;
+use crate::node::Node;
+use crate::related::{label_from_path, related_topics};
+use crate::topic::{compute_ordering, Status, Topic};
+use std::{
+	cell::RefCell,
+	collections::{HashMap, HashSet},
+	fs::File,
+	io::Write,
+	path::Path,
+	rc::Rc,
+};
+
+/// Columns of a board, in the order they are shown
+const COLUMNS: [Status; 4] = [
+	Status::Todo,
+	Status::InProgress,
+	Status::Blocked,
+	Status::Done,
+];
+
+/// Status of a column of a board, and tasks in it
+type Column = (Status, Vec<Rc<RefCell<Node<Topic>>>>);
+
+/// Check if a node requires an incomplete task or milestone; other nodes
+/// it requires are looked through to the tasks and milestones they
+/// require
+fn waiting_on_req(
+	node: &Node<Topic>,
+	nodes: &HashMap<String, Rc<RefCell<Node<Topic>>>>,
+	visited: &mut HashSet<String>,
+) -> bool {
+	node.req.iter().any(|r| {
+		let path = clean_path(r);
+		let p = match nodes.get(&path) {
+			Some(p) if visited.insert(path) => p.borrow(),
+			_ => return false,
+		};
+		let pdata = p.data();
+		if pdata.is_task() || pdata.is_milestone() {
+			!pdata.is_complete() && pdata.status != Status::Cancelled
+		} else {
+			waiting_on_req(&p, nodes, visited)
+		}
+	})
+}
+
+/// Column of a board in which a task belongs; a task is blocked if it is
+/// marked as blocked or waiting, or if any task or milestone it requires
+/// is incomplete; returns `None` for cancelled tasks and other nodes
+fn column_of(
+	node: &Node<Topic>,
+	nodes: &HashMap<String, Rc<RefCell<Node<Topic>>>>,
+) -> Option<Status> {
+	let data = node.data();
+	if !data.is_task() {
+		return None;
+	}
+	match data.status {
+		Status::Cancelled => return None,
+		Status::Done => return Some(Status::Done),
+		Status::Blocked | Status::Waiting => return Some(Status::Blocked),
+		_ => (),
+	}
+	if waiting_on_req(node, nodes, &mut HashSet::new()) {
+		Some(Status::Blocked)
+	} else {
+		Some(data.status)
+	}
+}
+
+/// Group tasks by column; tasks within each column are sorted using
+/// `compute_ordering`
+fn group_tasks(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>],
+	nodes: &HashMap<String, Rc<RefCell<Node<Topic>>>>,
+	reverse: bool,
+) -> Vec<Column> {
+	let mut columns: Vec<Column> =
+		COLUMNS.iter().map(|s| (*s, vec![])).collect();
+	for n in sorted_nodes.iter().rev() {
+		if let Some(c) = column_of(&n.borrow(), nodes) {
+			let i = COLUMNS.iter().position(|s| *s == c).unwrap();
+			columns[i].1.push(n.clone());
+		}
+	}
+	for (_, tasks) in columns.iter_mut() {
+		tasks.sort_by(|a, b| compute_ordering(reverse, a, b));
+	}
+	columns
+}
+
+/// Assignees and deadline of a task, e.g. `alice, bob; due 2026-10-25`
+fn card_details(data: &Topic) -> String {
+	let mut details = vec![];
+	if !data.assgn.is_empty() {
+		details.push(data.assgn.join(", "));
+	}
+	if let Some(dl) = data.deadline {
+		details.push(format!("due {}", dl));
+	}
+	details.join("; ")
+}
+
+/// Escape a string for a cell in a Markdown table
+fn markdown_cell(s: &str) -> String {
+	s.replace("|", "\\|").replace("\n", " ")
+}
+
+/// Escape a string for an HTML file
+fn html_text(s: &str) -> String {
+	s.replace("&", "&amp;")
+		.replace("<", "&lt;")
+		.replace(">", "&gt;")
+		.replace("\"", "&quot;")
+}
+
+/// Write a Markdown table with one column per status, with one task per
+/// cell
+fn write_markdown(
+	columns: &[Column],
+	file: &mut File,
+) {
+	let header: Vec<String> = columns
+		.iter()
+		.map(|(s, tasks)| format!("{} ({})", s.name(), tasks.len()))
+		.collect();
+	file
+		.write_all(format!("| {} |\n", header.join(" | ")).as_bytes())
+		.expect("");
+	file
+		.write_all(
+			format!("|{}\n", " --- |".repeat(columns.len())).as_bytes(),
+		)
+		.expect("");
+	let rows = columns.iter().map(|(_, t)| t.len()).max().unwrap_or(0);
+	for i in 0..rows {
+		let cells: Vec<String> = columns
+			.iter()
+			.map(|(_, tasks)| match tasks.get(i) {
+				Some(n) => {
+					let node = n.borrow();
+					let details = card_details(node.data());
+					if details.is_empty() {
+						format!("**{}**", markdown_cell(&node.data().label))
+					} else {
+						format!(
+							"**{}** ({})",
+							markdown_cell(&node.data().label),
+							markdown_cell(&details)
+						)
+					}
+				}
+				None => "".to_string(),
+			})
+			.collect();
+		file
+			.write_all(format!("| {} |\n", cells.join(" | ")).as_bytes())
+			.expect("");
+	}
+}
+
+/// Related topics of a task as HTML; tasks on the board are linked to,
+/// and other topics are named in plain text
+fn related_html(
+	path: &str,
+	related: &HashMap<String, Vec<String>>,
+	labels: &HashMap<String, String>,
+	on_board: &HashMap<String, String>,
+) -> String {
+	let links: Vec<String> = related
+		.get(path)
+		.into_iter()
+		.flatten()
+		.map(|r| match on_board.get(r) {
+			Some(label) => {
+				format!(
+					"<a href=\"#{}\">{}</a>",
+					html_text(r),
+					html_text(label)
+				)
+			}
+			None => html_text(
+				&labels.get(r).cloned().unwrap_or_else(|| label_from_path(r)),
+			),
+		})
+		.collect();
+	links.join(", ")
+}
+
+/// Write a standalone HTML page with one column of cards per status;
+/// cards link to the cards of related tasks
+fn write_html(
+	columns: &[Column],
+	title: &str,
+	related: &HashMap<String, Vec<String>>,
+	labels: &HashMap<String, String>,
+	file: &mut File,
+) {
+	let on_board: HashMap<String, String> = columns
+		.iter()
+		.flat_map(|(_, tasks)| tasks.iter())
+		.map(|n| (n.borrow().path.clone(), n.borrow().data().label.clone()))
+		.collect();
+	const STYLE: &str = "body{font-family:sans-serif;margin:1em;}\
+		.board{display:flex;gap:1em;align-items:flex-start;}\
+		.column{flex:1;background:#eee;border-radius:4px;padding:0.5em;}\
+		.column h2{font-size:1em;margin:0.25em;}\
+		.card{background:#fff;border-radius:4px;margin:0.5em 0;\
+		padding:0.5em;box-shadow:0 1px 2px #999;}\
+		.late{border-left:4px solid #c00;}\
+		.details,.file{color:#555;font-size:0.85em;}";
+	file
+		.write_all(
+			b"<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n",
+		)
+		.expect("");
+	file
+		.write_all(
+			format!("<title>{}</title>\n", html_text(title)).as_bytes(),
+		)
+		.expect("");
+	file
+		.write_all(format!("<style>{}</style>\n", STYLE).as_bytes())
+		.expect("");
+	file.write_all(b"</head>\n<body>\n").expect("");
+	file
+		.write_all(format!("<h1>{}</h1>\n", html_text(title)).as_bytes())
+		.expect("");
+	file.write_all(b"<div class=\"board\">\n").expect("");
+	for (status, tasks) in columns.iter() {
+		file.write_all(b"<div class=\"column\">\n").expect("");
+		file
+			.write_all(
+				format!("<h2>{} ({})</h2>\n", status.name(), tasks.len())
+					.as_bytes(),
+			)
+			.expect("");
+		for n in tasks.iter() {
+			let node = n.borrow();
+			let data = node.data();
+			let class = if data.is_late() { "card late" } else { "card" };
+			file
+				.write_all(
+					format!(
+						"<div class=\"{}\" id=\"{}\">\n",
+						class,
+						html_text(&node.path)
+					)
+					.as_bytes(),
+				)
+				.expect("");
+			file
+				.write_all(
+					format!("<strong>{}</strong>\n", html_text(&data.label))
+						.as_bytes(),
+				)
+				.expect("");
+			let details = card_details(data);
+			if !details.is_empty() {
+				file
+					.write_all(
+						format!(
+							"<div class=\"details\">{}</div>\n",
+							html_text(&details)
+						)
+						.as_bytes(),
+					)
+					.expect("");
+			}
+			let links = related_html(&node.path, related, labels, &on_board);
+			if !links.is_empty() {
+				file
+					.write_all(
+						format!(
+							"<div class=\"details\">Related: {}</div>\n",
+							links
+						)
+						.as_bytes(),
+					)
+					.expect("");
+			}
+			file
+				.write_all(
+					format!(
+						"<div class=\"file\">{}</div>\n",
+						html_text(&node.path)
+					)
+					.as_bytes(),
+				)
+				.expect("");
+			file.write_all(b"</div>\n").expect("");
+		}
+		file.write_all(b"</div>\n").expect("");
+	}
+	file.write_all(b"</div>\n</body>\n</html>\n").expect("");
+}
+
+/// Write a board of tasks grouped by status to a Markdown table
+/// (board.md) and a standalone HTML page (board.html), and print a
+/// compact view of it; `nodes` are all nodes read, keyed by path
+pub fn write_board(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>],
+	nodes: &HashMap<String, Rc<RefCell<Node<Topic>>>>,
+	title: &str,
+	reverse: bool,
+) {
+	let columns = group_tasks(sorted_nodes, nodes, reverse);
+	let title = if title.is_empty() { "Board" } else { title };
+	let related = related_topics(sorted_nodes);
+	let labels: HashMap<String, String> = sorted_nodes
+		.iter()
+		.map(|n| (n.borrow().path.clone(), n.borrow().data().label.clone()))
+		.collect();
+
+	let path = Path::new("../output/board.md");
+	let mut file = File::create(path).expect("could not create .md file");
+	write_markdown(&columns, &mut file);
+
+	let path = Path::new("../output/board.html");
+	let mut file =
+		File::create(path).expect("could not create .html file");
+	write_html(&columns, title, &related, &labels, &mut file);
+
+	print_board(&columns);
+}
+
+/// Print one line per task under a heading for each column
+fn print_board(columns: &[Column]) {
+	println!("========================================");
+	for (status, tasks) in columns.iter() {
+		println!("{} ({})", status.name(), tasks.len());
+		for n in tasks.iter() {
+			let node = n.borrow();
+			let details = card_details(node.data());
+			if details.is_empty() {
+				println!("  - {}", node.data().label);
+			} else {
+				println!("  - {} ({})", node.data().label, details);
+			}
+		}
+	}
+}
@@ -0,0 +1,260 @@
+use crate::calendar::format_days;
+use crate::node::Node;
+use crate::options::Options;
+use crate::topic::{Status, Topic};
+use chrono::{Duration, NaiveDate};
+use std::{cell::RefCell, fs::File, io::Write, path::Path, rc::Rc};
+
+/// Work completed in a week, and work remaining at the end of the week
+pub struct Week {
+	/// First day of the week
+	pub start: NaiveDate,
+	/// Last day of the week (inclusive); the last week may be cut short
+	pub end: NaiveDate,
+	/// Number of tasks completed during the week
+	pub completed: usize,
+	/// Expected duration of tasks completed during the week, in working
+	/// days
+	pub completed_work: f64,
+	/// Expected duration of tasks not yet completed at the end of the
+	/// week, in working days
+	pub remaining: f64,
+}
+
+/// Tasks counted towards a burndown, i.e. tasks that are not cancelled
+fn counted(data: &Topic) -> bool {
+	data.is_task() && data.status != Status::Cancelled
+}
+
+/// Check if a task was complete by the end of a given day; completed
+/// tasks without a completion date are considered complete all along
+fn complete_by(
+	data: &Topic,
+	date: NaiveDate,
+) -> bool {
+	data.is_complete() && data.complete.is_none_or(|c| c <= date)
+}
+
+/// First date of work on any task, i.e. the earliest start or completion
+/// date, if any
+fn first_work_date(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>]
+) -> Option<NaiveDate> {
+	sorted_nodes
+		.iter()
+		.filter(|n| counted(n.borrow().data()))
+		.filter_map(|n| {
+			let n = n.borrow();
+			n.data().start.into_iter().chain(n.data().complete).min()
+		})
+		.min()
+}
+
+/// First and last day of a burndown; defaults to the first date of work
+/// on any task and `today`; a default first day after the last day is
+/// moved to the last day
+pub fn burndown_range(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>],
+	options: &Options,
+	today: NaiveDate,
+) -> (NaiveDate, NaiveDate) {
+	let to = options.to.unwrap_or(today);
+	if let Some(from) = options.from.filter(|from| *from > to) {
+		match options.to {
+			Some(_) => {
+				panic!("--from ({}) must not be after --to ({})", from, to)
+			}
+			None => panic!(
+				"--from ({}) must not be after today ({}); give --to",
+				from, to
+			),
+		}
+	}
+	let from = options
+		.from
+		.or_else(|| first_work_date(sorted_nodes))
+		.unwrap_or(to)
+		.min(to);
+	(from, to)
+}
+
+/// Expected duration of tasks not complete by the end of a given day
+fn remaining_work(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>],
+	date: NaiveDate,
+) -> f64 {
+	sorted_nodes
+		.iter()
+		.map(|n| n.borrow())
+		.filter(|n| counted(n.data()) && !complete_by(n.data(), date))
+		.map(|n| n.data().expected)
+		.sum()
+}
+
+/// Compute work completed in each week from `from` to `to` (inclusive),
+/// and work remaining at the end of each week, using completion dates
+/// and expected durations of tasks; weeks start on the day of the week
+/// of `from`
+pub fn compute_burndown(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>],
+	from: NaiveDate,
+	to: NaiveDate,
+) -> Vec<Week> {
+	if from > to {
+		panic!("Burndown must start ({}) before it ends ({})", from, to);
+	}
+	let mut weeks = vec![];
+	let mut start = from;
+	while start <= to {
+		let end = (start + Duration::days(6)).min(to);
+		let mut week = Week {
+			start,
+			end,
+			completed: 0,
+			completed_work: 0.0,
+			remaining: remaining_work(sorted_nodes, end),
+		};
+		for n in sorted_nodes.iter() {
+			let data = n.borrow();
+			let data = data.data();
+			if !counted(data) || !data.is_complete() {
+				continue;
+			}
+			if data.complete.is_some_and(|c| c >= start && c <= end) {
+				week.completed += 1;
+				week.completed_work += data.expected;
+			}
+		}
+		weeks.push(week);
+		start = end + Duration::days(1);
+	}
+	weeks
+}
+
+/// Write one row per week to a CSV file
+fn write_csv(
+	weeks: &[Week],
+	file: &mut File,
+) {
+	file
+		.write_all(
+			b"week_start,week_end,completed,completed_work,remaining\n",
+		)
+		.expect("");
+	for w in weeks.iter() {
+		let row = [
+			w.start.to_string(),
+			w.end.to_string(),
+			w.completed.to_string(),
+			format_days(w.completed_work),
+			format_days(w.remaining),
+		];
+		file.write_all(row.join(",").as_bytes()).expect("");
+		file.write_all(b"\n").expect("");
+	}
+}
+
+/// Write a chart of remaining work (as a line, at the start of the first
+/// week and at the end of each week) and tasks completed per week (as
+/// bars) using the `pgfplots` package
+pub fn write_burndown_chart(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>],
+	weeks: &[Week],
+	file: &mut File,
+) {
+	if weeks.is_empty() {
+		return;
+	}
+
+	// Ticks mark boundaries between weeks
+	let mut ticks: Vec<String> = vec![weeks[0].start.to_string()];
+	ticks.extend(
+		weeks
+			.iter()
+			.map(|w| (w.end + Duration::days(1)).to_string()),
+	);
+	let initial =
+		remaining_work(sorted_nodes, weeks[0].start - Duration::days(1));
+	let mut remaining = format!("(0,{})", format_days(initial));
+	for (i, w) in weeks.iter().enumerate() {
+		remaining.push_str(&format!(
+			" ({},{})",
+			i + 1,
+			format_days(w.remaining)
+		));
+	}
+	let completed: Vec<String> = weeks
+		.iter()
+		.enumerate()
+		.map(|(i, w)| format!("({}.5,{})", i, w.completed))
+		.collect();
+
+	let axis = format!(
+		"width=\\textwidth, height=7cm, xmin=0, xmax={}, ymin=0, \
+		 xtick={{0,...,{}}}, xticklabels={{{}}}, \
+		 x tick label style={{rotate=90, font=\\tiny}}",
+		weeks.len(),
+		weeks.len(),
+		ticks.join(","),
+	);
+	file
+		.write_all(b"\\noindent\n\\begin{tikzpicture}\n")
+		.expect("");
+	file.write_all(b"\\begin{axis}[").expect("");
+	file.write_all(axis.as_bytes()).expect("");
+	file
+		.write_all(
+			b", axis y line*=left, ylabel={Remaining work (days)}]\n\
+			  \\addplot[thick, mark=*, blue] coordinates {",
+		)
+		.expect("");
+	file.write_all(remaining.as_bytes()).expect("");
+	file.write_all(b"};\n\\end{axis}\n").expect("");
+	file.write_all(b"\\begin{axis}[").expect("");
+	file.write_all(axis.as_bytes()).expect("");
+	file
+		.write_all(
+			b", axis y line*=right, axis x line=none, \
+			  ylabel={Tasks completed}, ybar, bar width=6pt]\n\
+			  \\addplot[fill=gray!50, draw=none] coordinates {",
+		)
+		.expect("");
+	file.write_all(completed.join(" ").as_bytes()).expect("");
+	file.write_all(b"};\n\\end{axis}\n").expect("");
+	file.write_all(b"\\end{tikzpicture}\n\n").expect("");
+}
+
+/// Write work completed and remaining per week to burndown.csv, and a
+/// chart of it to burndown.tex, which can be included in a TeX document
+/// using `\input{burndown}`
+pub fn write_burndown(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>],
+	weeks: &[Week],
+) {
+	let path = Path::new("../output/burndown.csv");
+	let mut file =
+		File::create(path).expect("could not create .csv file");
+	write_csv(weeks, &mut file);
+
+	let path = Path::new("../output/burndown.tex");
+	let mut file =
+		File::create(path).expect("could not create .tex file");
+	write_burndown_chart(sorted_nodes, weeks, &mut file);
+}
+
+/// Print work completed and remaining per week
+pub fn print_burndown(weeks: &[Week]) {
+	println!("========================================");
+	println!("Burndown:");
+	println!("WEEK | COMPLETED | COMPLETED WORK | REMAINING WORK");
+	for w in weeks.iter() {
+		println!(
+			"{} to {} | {} | {} | {}",
+			w.start,
+			w.end,
+			w.completed,
+			format_days(w.completed_work),
+			format_days(w.remaining),
+		);
+	}
+}
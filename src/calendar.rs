@@ -0,0 +1,325 @@
+use crate::date::parse_date;
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Weekday};
+use serde_yaml::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Tolerance when comparing fractions of working days
+const EPSILON: f64 = 1e-9;
+
+/// Day after a given date
+fn next_day(date: NaiveDate) -> NaiveDate {
+	date
+		.succ_opt()
+		.unwrap_or_else(|| panic!("No day after {}", date))
+}
+
+/// Days and hours of work in a project; durations are measured in
+/// working days, i.e. days on which work is done
+pub struct Calendar {
+	/// Days of the week on which work is done, starting with Monday
+	pub workdays: [bool; 7],
+	/// Number of working hours in a working day
+	pub hours_per_day: f64,
+	/// Days on which nobody works
+	pub holidays: HashSet<NaiveDate>,
+	/// Days on which individual assignees do not work
+	pub time_off: HashMap<String, HashSet<NaiveDate>>,
+}
+
+impl Default for Calendar {
+	fn default() -> Self {
+		Calendar::new()
+	}
+}
+
+/// A point in working time: a date and the fraction of that day's
+/// working hours already spent
+#[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+pub struct WorkTime {
+	pub date: NaiveDate,
+	pub used: f64,
+}
+
+impl WorkTime {
+	/// Start of a day
+	pub fn from_date(date: NaiveDate) -> WorkTime {
+		WorkTime { date, used: 0.0 }
+	}
+
+	/// Later of two points in working time
+	pub fn max(
+		self,
+		other: WorkTime,
+	) -> WorkTime {
+		if other > self {
+			other
+		} else {
+			self
+		}
+	}
+
+	/// Date by which work ending at this point is finished; end dates are
+	/// exclusive, so work ending part way through a day is finished on the
+	/// following day
+	pub fn end_date(&self) -> NaiveDate {
+		if self.used > EPSILON {
+			self.date + Duration::days(1)
+		} else {
+			self.date
+		}
+	}
+}
+
+impl Calendar {
+	/// Calendar in which every day is a working day, so that working days
+	/// are calendar days
+	pub fn new() -> Calendar {
+		Calendar {
+			workdays: [true; 7],
+			hours_per_day: 8.0,
+			holidays: HashSet::new(),
+			time_off: HashMap::new(),
+		}
+	}
+
+	/// Read calendar from the `calendar` section of the project
+	/// configuration; weekends are not working days unless `workdays` is
+	/// given
+	pub fn from_yaml(value: &Value) -> Result<Calendar, String> {
+		let mut calendar = Calendar::new();
+		calendar.workdays = [true, true, true, true, true, false, false];
+		let map = value
+			.as_mapping()
+			.ok_or("expected a map of calendar settings")?;
+		for (k, v) in map.iter() {
+			match k.as_str().unwrap_or("") {
+				"workdays" => {
+					calendar.workdays = [false; 7];
+					let days: Vec<String> = serde_yaml::from_value(v.clone())
+						.map_err(|_| "expected a list of weekdays")?;
+					for d in days.iter() {
+						let weekday = d
+							.parse::<Weekday>()
+							.map_err(|_| format!("`{}` is not a weekday", d))?;
+						calendar.workdays
+							[weekday.num_days_from_monday() as usize] = true;
+					}
+					if !calendar.workdays.iter().any(|x| *x) {
+						return Err("no working days in a week".to_string());
+					}
+				}
+				"hours_per_day" => {
+					calendar.hours_per_day = v
+						.as_f64()
+						.filter(|h| *h > 0.0 && *h <= 24.0)
+						.ok_or("expected hours_per_day between 0 and 24")?;
+				}
+				"holidays" => calendar.holidays = parse_days(v)?,
+				"time_off" => {
+					let people = v
+						.as_mapping()
+						.ok_or("expected a map of assignees to days off")?;
+					for (person, days) in people.iter() {
+						let name = person
+							.as_str()
+							.ok_or("expected name of assignee")?
+							.to_string();
+						calendar.time_off.insert(name, parse_days(days)?);
+					}
+				}
+				other => {
+					return Err(format!("unknown calendar setting `{}`", other))
+				}
+			}
+		}
+		Ok(calendar)
+	}
+
+	/// Check if a date is a working day for all of the given assignees
+	pub fn is_working_day(
+		&self,
+		date: NaiveDate,
+		assgn: &[String],
+	) -> bool {
+		self.workdays[date.weekday().num_days_from_monday() as usize]
+			&& !self.holidays.contains(&date)
+			&& !assgn.iter().any(|a| {
+				self.time_off.get(a).is_some_and(|d| d.contains(&date))
+			})
+	}
+
+	/// Number of working days from the start of `from` to the start of
+	/// `to`
+	pub fn working_days_between(
+		&self,
+		from: NaiveDate,
+		to: NaiveDate,
+		assgn: &[String],
+	) -> usize {
+		let mut days = 0;
+		let mut date = from;
+		while date < to {
+			if self.is_working_day(date, assgn) {
+				days += 1;
+			}
+			date = next_day(date);
+		}
+		days
+	}
+
+	/// Working time spent between two points in time, in working days;
+	/// times of day, if both are given, count as working hours
+	pub fn working_time_between(
+		&self,
+		from: (NaiveDate, Option<NaiveTime>),
+		to: (NaiveDate, Option<NaiveTime>),
+		assgn: &[String],
+	) -> f64 {
+		let days = self.working_days_between(from.0, to.0, assgn) as f64;
+		match (from.1, to.1) {
+			(Some(a), Some(b)) => {
+				let hours = (b - a).num_minutes() as f64 / 60.0;
+				(days + hours / self.hours_per_day).max(0.0)
+			}
+			_ => days,
+		}
+	}
+
+	/// Point in working time at which work of the given duration (in
+	/// working days), started at `from`, is finished, if the given
+	/// assignees work on it
+	pub fn advance(
+		&self,
+		from: WorkTime,
+		days: f64,
+		assgn: &[String],
+	) -> WorkTime {
+		let mut date = from.date;
+		let mut used = from.used;
+		let mut remaining = days;
+		let mut skipped = 0;
+		while remaining > EPSILON {
+			if self.is_working_day(date, assgn) && used < 1.0 - EPSILON {
+				let spent = remaining.min(1.0 - used);
+				used += spent;
+				remaining -= spent;
+				skipped = 0;
+				if remaining <= EPSILON {
+					break;
+				}
+			} else {
+				skipped += 1;
+				if skipped > 3660 {
+					panic!(
+						"No working days for {} after {}; check calendar in \
+						 project configuration",
+						assgn.join(", "),
+						date
+					);
+				}
+			}
+			date = next_day(date);
+			used = 0.0;
+		}
+		if used >= 1.0 - EPSILON {
+			WorkTime::from_date(next_day(date))
+		} else {
+			WorkTime { date, used }
+		}
+	}
+
+	/// First point in working time, no earlier than `from`, at which the
+	/// given assignees can work
+	pub fn next_working(
+		&self,
+		from: WorkTime,
+		assgn: &[String],
+	) -> WorkTime {
+		let mut t = from;
+		let mut skipped = 0;
+		while !self.is_working_day(t.date, assgn) || t.used >= 1.0 - EPSILON
+		{
+			t = WorkTime::from_date(next_day(t.date));
+			skipped += 1;
+			if skipped > 3660 {
+				panic!(
+					"No working days for {} after {}; check calendar in \
+					 project configuration",
+					assgn.join(", "),
+					from.date
+				);
+			}
+		}
+		t
+	}
+}
+
+/// Parse a list of days, given as dates or as ranges of dates in the form
+/// `{from: DATE, to: DATE}` (inclusive)
+fn parse_days(value: &Value) -> Result<HashSet<NaiveDate>, String> {
+	let mut days = HashSet::new();
+	let list = value.as_sequence().ok_or("expected a list of dates")?;
+	for item in list.iter() {
+		match item.as_mapping() {
+			Some(range) => {
+				let get = |k: &str| -> Result<NaiveDate, String> {
+					let v = range
+						.get(&Value::String(k.to_string()))
+						.ok_or(format!("expected `{}` date in range", k))?;
+					Ok(parse_date(v)?.0)
+				};
+				let (from, to) = (get("from")?, get("to")?);
+				let mut date = from;
+				while date <= to {
+					days.insert(date);
+					date = next_day(date);
+				}
+			}
+			None => {
+				days.insert(parse_date(item)?.0);
+			}
+		}
+	}
+	Ok(days)
+}
+
+/// Parse a duration in working days from a YAML value; numbers are days,
+/// and strings may give a unit, e.g. `3 days`, `1.5d`, `6 hours`, or
+/// `4h`
+pub fn parse_duration(
+	value: &Value,
+	hours_per_day: f64,
+) -> Result<f64, String> {
+	let invalid = || {
+		"expected a number of days, or a duration such as `3 days` or \
+		 `4 hours`"
+			.to_string()
+	};
+	let days = match value {
+		Value::Number(n) => n.as_f64().ok_or_else(invalid)?,
+		Value::String(s) => {
+			let s = s.trim().to_lowercase();
+			let split = s
+				.find(|c: char| !(c.is_ascii_digit() || c == '.'))
+				.unwrap_or(s.len());
+			let number: f64 =
+				s[..split].trim().parse().map_err(|_| invalid())?;
+			match s[split..].trim() {
+				"" | "d" | "day" | "days" => number,
+				"h" | "hr" | "hrs" | "hour" | "hours" => number / hours_per_day,
+				_ => return Err(invalid()),
+			}
+		}
+		_ => return Err(invalid()),
+	};
+	if days < 0.0 {
+		return Err("duration must not be negative".to_string());
+	}
+	Ok(days)
+}
+
+/// Format a number of working days for display, without trailing zeros
+pub fn format_days(days: f64) -> String {
+	let s = format!("{:.2}", days);
+	s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
@@ -0,0 +1,56 @@
+use crate::calendar::Calendar;
+use crate::environments::{
+	default_environments, environments_from_yaml, Environment,
+};
+use serde_yaml::Value;
+use std::{collections::HashMap, fs::File, io::Read, path::Path};
+
+/// Settings that apply to a whole project, read from `../tok.yml`
+pub struct Config {
+	/// Working days and hours, used to schedule tasks and measure their
+	/// durations
+	pub calendar: Calendar,
+	/// How nodes are written to a TeX file, keyed by prefix of file names
+	pub environments: HashMap<String, Environment>,
+}
+
+impl Config {
+	/// Read project configuration, or use defaults if there is none
+	pub fn load() -> Config {
+		let mut config = Config {
+			calendar: Calendar::new(),
+			environments: default_environments(),
+		};
+		let path = Path::new("../tok.yml");
+		if !path.is_file() {
+			return config;
+		}
+		println!("Using project configuration {}", path.display());
+		let mut contents = String::new();
+		File::open(path)
+			.expect("Cannot open project configuration")
+			.read_to_string(&mut contents)
+			.expect("Cannot read project configuration");
+		let yaml: Value =
+			serde_yaml::from_str(&contents).unwrap_or_else(|e| {
+				panic!(
+					"Invalid project configuration {}: {}",
+					path.display(),
+					e
+				)
+			});
+		if let Some(calendar) = yaml.get("calendar") {
+			config.calendar =
+				Calendar::from_yaml(calendar).unwrap_or_else(|e| {
+					panic!("Invalid calendar in {}: {}", path.display(), e)
+				});
+		}
+		if let Some(environments) = yaml.get("environments") {
+			config.environments = environments_from_yaml(environments)
+				.unwrap_or_else(|e| {
+					panic!("Invalid environments in {}: {}", path.display(), e)
+				});
+		}
+		config
+	}
+}
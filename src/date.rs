@@ -0,0 +1,187 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime};
+use serde_yaml::Value;
+
+/// Parse a date, with an optional time of day, from a YAML value; dates
+/// may be written as a sequence `[YYYY, MM, DD]` or as an ISO 8601
+/// string, e.g. `2024-05-01`, `2024-05-01T17:00`, or
+/// `2024-05-01 17:00:00`
+pub fn parse_date(
+	value: &Value
+) -> Result<(NaiveDate, Option<NaiveTime>), String> {
+	match value {
+		Value::Sequence(seq) => {
+			let ymd: Vec<u64> =
+				seq.iter().filter_map(|x| x.as_u64()).collect();
+			if seq.len() != 3 || ymd.len() != 3 {
+				return Err(
+					"expected a date in the form [YYYY, MM, DD]".to_string(),
+				);
+			}
+			let date = NaiveDate::from_ymd_opt(
+				ymd[0] as i32,
+				ymd[1] as u32,
+				ymd[2] as u32,
+			)
+			.ok_or(format!(
+				"[{}, {}, {}] is not a valid date",
+				ymd[0], ymd[1], ymd[2]
+			))?;
+			Ok((date, None))
+		}
+		Value::String(s) => parse_date_str(s.trim()),
+		_ => Err(
+			"expected a date in the form YYYY-MM-DD or [YYYY, MM, DD]"
+				.to_string(),
+		),
+	}
+}
+
+/// Parse an ISO 8601 date with an optional time of day
+fn parse_date_str(
+	s: &str
+) -> Result<(NaiveDate, Option<NaiveTime>), String> {
+	let invalid = || {
+		format!(
+			"`{}` is not a valid date in the form YYYY-MM-DD or \
+			 YYYY-MM-DDTHH:MM",
+			s
+		)
+	};
+	let (date_str, time_str) = match s.find(['T', ' ']) {
+		Some(i) => (&s[..i], Some(s[i + 1..].trim())),
+		None => (s, None),
+	};
+	let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
+		.map_err(|_| invalid())?;
+	let time = match time_str {
+		Some(t) => Some(
+			NaiveTime::parse_from_str(t, "%H:%M:%S")
+				.or_else(|_| NaiveTime::parse_from_str(t, "%H:%M"))
+				.map_err(|_| invalid())?,
+		),
+		None => None,
+	};
+	Ok((date, time))
+}
+
+/// Format a number of days for display, e.g. `1 day` or `3 days`
+pub fn format_day_count(days: i64) -> String {
+	if days == 1 {
+		"1 day".to_string()
+	} else {
+		format!("{} days", days)
+	}
+}
+
+/// Format a date, with an optional time of day, for display in a
+/// document or in the terminal
+pub fn format_date(
+	date: NaiveDate,
+	time: Option<NaiveTime>,
+) -> String {
+	match time {
+		Some(t) => format!("{} {}", date, t.format("%H:%M")),
+		None => date.to_string(),
+	}
+}
+
+/// Interval between occurrences of a repeating task
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Repeat {
+	Days(i64),
+	Months(u32),
+}
+
+impl Repeat {
+	/// Parse an interval such as `daily`, `weekly`, `monthly`, `yearly`,
+	/// or `every N days`, `every N weeks`, or `every N months`
+	pub fn parse(s: &str) -> Result<Repeat, String> {
+		let invalid = || {
+			format!(
+				"`{}` is not one of daily, weekly, monthly, yearly, or every N \
+				 days/weeks/months",
+				s
+			)
+		};
+		let words: Vec<String> = s
+			.to_lowercase()
+			.split_whitespace()
+			.map(String::from)
+			.collect();
+		let words: Vec<&str> = words.iter().map(|w| w.as_str()).collect();
+		let repeat = match words.as_slice() {
+			["daily"] => Repeat::Days(1),
+			["weekly"] => Repeat::Days(7),
+			["monthly"] => Repeat::Months(1),
+			["yearly"] | ["annually"] => Repeat::Months(12),
+			["every", unit] => parse_unit(1, unit).ok_or_else(invalid)?,
+			["every", n, unit] => {
+				let n: u32 = n.parse().map_err(|_| invalid())?;
+				parse_unit(n, unit).ok_or_else(invalid)?
+			}
+			_ => return Err(invalid()),
+		};
+		match repeat {
+			Repeat::Days(0) | Repeat::Months(0) => Err(invalid()),
+			r => Ok(r),
+		}
+	}
+
+	/// Date of the `n`th occurrence after the first; monthly occurrences
+	/// fall on the last day of shorter months
+	pub fn nth(
+		&self,
+		first: NaiveDate,
+		n: u32,
+	) -> NaiveDate {
+		match self {
+			Repeat::Days(d) => first + Duration::days(d * n as i64),
+			Repeat::Months(m) => add_months(first, m * n),
+		}
+	}
+
+	/// Description of the interval for display in a document
+	pub fn describe(&self) -> String {
+		match self {
+			Repeat::Days(1) => "daily".to_string(),
+			Repeat::Days(7) => "weekly".to_string(),
+			Repeat::Days(d) if d % 7 == 0 => format!("every {} weeks", d / 7),
+			Repeat::Days(d) => format!("every {} days", d),
+			Repeat::Months(1) => "monthly".to_string(),
+			Repeat::Months(12) => "yearly".to_string(),
+			Repeat::Months(m) => format!("every {} months", m),
+		}
+	}
+}
+
+/// Interval of `n` units of days, weeks, or months
+fn parse_unit(
+	n: u32,
+	unit: &str,
+) -> Option<Repeat> {
+	match unit.trim_end_matches('s') {
+		"day" => Some(Repeat::Days(n as i64)),
+		"week" => Some(Repeat::Days(7 * n as i64)),
+		"month" => Some(Repeat::Months(n)),
+		"year" => Some(Repeat::Months(12 * n)),
+		_ => None,
+	}
+}
+
+/// Add months to a date, moving to the last day of the month if the day
+/// does not exist in that month
+fn add_months(
+	date: NaiveDate,
+	months: u32,
+) -> NaiveDate {
+	let index = date.year() * 12 + date.month0() as i32 + months as i32;
+	let (year, month) =
+		(index.div_euclid(12), index.rem_euclid(12) as u32 + 1);
+	let mut day = date.day();
+	loop {
+		if let Some(d) = NaiveDate::from_ymd_opt(year, month, day) {
+			return d;
+		}
+		day -= 1;
+	}
+}
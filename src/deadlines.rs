@@ -0,0 +1,92 @@
+use crate::calendar::{Calendar, WorkTime};
+use crate::node::Node;
+use crate::topic::Topic;
+use chrono::NaiveDate;
+use std::{
+	cell::RefCell, cmp::max, collections::HashMap, collections::HashSet,
+	rc::Rc,
+};
+
+/// Deadline information propagated from the predecessors of a node
+#[derive(Clone, Copy)]
+struct Propagated {
+	/// Latest deadline of this node or any of its predecessors
+	latest_deadline: Option<NaiveDate>,
+	/// Number of working days (sum of remaining work in open tasks) along
+	/// the longest chain ending at this node
+	remaining_days: f64,
+}
+
+/// Flag nodes whose deadlines cannot be met; a deadline is inverted if
+/// any (direct or indirect) predecessor has a later deadline, and
+/// infeasible if the longest chain of open tasks leading up to and
+/// including an open task or unreached milestone cannot be completed
+/// before the deadline when starting on `today` and working only on
+/// working days
+pub fn flag_deadlines(
+	root: Rc<RefCell<Node<Topic>>>,
+	today: NaiveDate,
+	calendar: &Calendar,
+) {
+	let mut memo: HashMap<String, Propagated> = HashMap::new();
+	let mut branch: HashSet<String> = HashSet::new();
+	propagate_deadlines(root, today, calendar, &mut memo, &mut branch);
+}
+
+/// Visit predecessors of `node` first, then set deadline flags for
+/// `node` based on information propagated from its predecessors;
+/// ignores cycles
+fn propagate_deadlines(
+	node: Rc<RefCell<Node<Topic>>>,
+	today: NaiveDate,
+	calendar: &Calendar,
+	memo: &mut HashMap<String, Propagated>,
+	branch: &mut HashSet<String>,
+) -> Propagated {
+	let node_path = node.borrow().path.clone();
+	if let Some(p) = memo.get(&node_path) {
+		return *p;
+	}
+
+	// Gather latest deadline and longest chain of work from predecessors
+	branch.insert(node_path.clone());
+	let mut req_latest_deadline: Option<NaiveDate> = None;
+	let mut req_remaining_days: f64 = 0.0;
+	for p in node.borrow().predecessors() {
+		let cycle = branch.contains(&p.borrow().path);
+		if !cycle {
+			let propagated =
+				propagate_deadlines(p, today, calendar, memo, branch);
+			req_latest_deadline =
+				max(req_latest_deadline, propagated.latest_deadline);
+			req_remaining_days =
+				req_remaining_days.max(propagated.remaining_days);
+		}
+	}
+	branch.remove(&node_path);
+
+	// Set flags for this node
+	let deadline = node.borrow().data().deadline;
+	let remaining_days =
+		req_remaining_days + node.borrow().data().remaining();
+	if let Some(dl) = deadline {
+		let mut n = node.borrow_mut();
+		let data = n.data_mut();
+		if req_latest_deadline > Some(dl) {
+			data.req_deadline = req_latest_deadline;
+		}
+		let earliest_finish = calendar
+			.advance(WorkTime::from_date(today), remaining_days, &[])
+			.end_date();
+		if data.is_pending() && earliest_finish > dl {
+			data.earliest_finish = Some(earliest_finish);
+		}
+	}
+
+	let propagated = Propagated {
+		latest_deadline: max(req_latest_deadline, deadline),
+		remaining_days,
+	};
+	memo.insert(node_path, propagated);
+	propagated
+}
@@ -0,0 +1,109 @@
+use crate::node::Node;
+use crate::related::{label_from_path, related_topics};
+use crate::topic::Topic;
+use std::{
+	cell::RefCell, collections::HashSet, fs::File, io::Write, path::Path,
+	rc::Rc,
+};
+
+/// Quote a string for a DOT file
+fn quote(s: &str) -> String {
+	format!("\"{}\"", s.replace("\\", "\\\\").replace("\"", "\\\""))
+}
+
+/// Write the dependency graph to a DOT file (main.dot) that can be drawn
+/// using Graphviz; an arrow points from each node to the nodes that come
+/// after it because of it, and related topics are joined by dashed lines
+/// that do not affect the layout; related topics that are not in the
+/// document are drawn with dashed outlines
+pub fn write_dot(sorted_nodes: &[Rc<RefCell<Node<Topic>>>]) {
+	let path = Path::new("../output/main.dot");
+	let mut file =
+		File::create(path).expect("could not create .dot file");
+	file
+		.write_all(b"digraph tok {\n\tnode [shape=box];\n")
+		.expect("");
+
+	// Nodes, in document order
+	let in_document: HashSet<String> = sorted_nodes
+		.iter()
+		.map(|n| n.borrow().path.clone())
+		.collect();
+	for n in sorted_nodes.iter().rev() {
+		let node = n.borrow();
+		file
+			.write_all(
+				format!(
+					"\t{} [label={}];\n",
+					quote(&node.path),
+					quote(&node.data().label)
+				)
+				.as_bytes(),
+			)
+			.expect("");
+	}
+
+	// Dependencies
+	for n in sorted_nodes.iter().rev() {
+		let node = n.borrow();
+		for p in node.predecessors().iter() {
+			let p = p.borrow();
+			if !in_document.contains(&p.path) {
+				continue;
+			}
+			file
+				.write_all(
+					format!("\t{} -> {};\n", quote(&p.path), quote(&node.path))
+						.as_bytes(),
+				)
+				.expect("");
+		}
+	}
+
+	// Related topics, each pair once
+	let related = related_topics(sorted_nodes);
+	let mut outside: Vec<&String> = vec![];
+	let mut pairs: Vec<(&String, &String)> = vec![];
+	for n in sorted_nodes.iter().rev() {
+		let path = n.borrow().path.clone();
+		let (a, list) = match related.get_key_value(&path) {
+			Some(x) => x,
+			None => continue,
+		};
+		for b in list.iter() {
+			if !in_document.contains(b) {
+				if !outside.contains(&b) {
+					outside.push(b);
+				}
+				pairs.push((a, b));
+			} else if !pairs.contains(&(b, a)) {
+				pairs.push((a, b));
+			}
+		}
+	}
+	for b in outside.iter() {
+		file
+			.write_all(
+				format!(
+					"\t{} [label={}, style=dashed];\n",
+					quote(b),
+					quote(&label_from_path(b))
+				)
+				.as_bytes(),
+			)
+			.expect("");
+	}
+	for (a, b) in pairs.iter() {
+		file
+			.write_all(
+				format!(
+					"\t{} -> {} [style=dashed, dir=none, constraint=false];\n",
+					quote(a),
+					quote(b)
+				)
+				.as_bytes(),
+			)
+			.expect("");
+	}
+	file.write_all(b"}\n").expect("");
+}
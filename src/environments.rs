@@ -0,0 +1,154 @@
+use serde_yaml::Value;
+use std::collections::HashMap;
+
+/// Prefixes written by `tex::write_to_tex` itself, which cannot be
+/// redefined
+const BUILT_IN: [&str; 11] = [
+	"task", "done", "ms", "ex", "mot", "abs", "alg", "lst", "lstfile",
+	"plain", "x",
+];
+
+/// How nodes with a given prefix are written to a TeX file
+#[derive(Clone)]
+pub struct Environment {
+	/// LaTeX environment, e.g. `theorem`
+	pub name: String,
+	/// Prefix of the label of each node, e.g. `thm` for `\label{thm:...}`;
+	/// nodes are not labelled if empty
+	pub label: String,
+	/// Whether the node's label is given as the environment's title
+	pub title: bool,
+	/// Whether proofs listed under `pfs` follow the environment
+	pub proofs: bool,
+	/// Whether the environment is left out in crib mode
+	pub hide_in_crib: bool,
+	/// Whether a link to Wikipedia may follow the environment
+	pub wiki: bool,
+	/// If given, the environment is defined in the preamble as a
+	/// theorem-like environment with this heading, e.g. `Axiom`, numbered
+	/// along with theorems
+	pub heading: String,
+}
+
+impl Environment {
+	fn new(
+		name: &str,
+		label: &str,
+		proofs: bool,
+	) -> Environment {
+		Environment {
+			name: name.to_string(),
+			label: label.to_string(),
+			title: true,
+			proofs,
+			hide_in_crib: false,
+			wiki: true,
+			heading: String::new(),
+		}
+	}
+
+	/// Read settings of an environment, starting from `env`
+	pub fn from_yaml(
+		mut env: Environment,
+		value: &Value,
+	) -> Result<Environment, String> {
+		let map = value
+			.as_mapping()
+			.ok_or("expected a map of environment settings")?;
+		for (k, v) in map.iter() {
+			let key = k.as_str().unwrap_or("");
+			let string = || {
+				v.as_str()
+					.map(|s| s.to_string())
+					.ok_or(format!("expected text for `{}`", key))
+			};
+			let flag = || {
+				v.as_bool()
+					.ok_or(format!("expected true or false for `{}`", key))
+			};
+			match key {
+				"env" => env.name = string()?,
+				"label" => env.label = string()?,
+				"title" => env.title = flag()?,
+				"proofs" => env.proofs = flag()?,
+				"hide_in_crib" => env.hide_in_crib = flag()?,
+				"wiki" => env.wiki = flag()?,
+				"heading" => env.heading = string()?,
+				other => {
+					return Err(format!(
+						"unknown environment setting `{}`",
+						other
+					))
+				}
+			}
+		}
+		if env.name.is_empty() {
+			return Err("LaTeX environment must not be empty".to_string());
+		}
+		Ok(env)
+	}
+}
+
+/// Environments available without configuration, keyed by prefix
+pub fn default_environments() -> HashMap<String, Environment> {
+	let mut envs = HashMap::new();
+	let mut add = |prefix: &str, env: Environment| {
+		envs.insert(prefix.to_string(), env);
+	};
+	add("def", Environment::new("definition", "def", false));
+	add("eg", Environment::new("example", "eg", false));
+	add("lem", Environment::new("lemma", "lem", true));
+	add("thm", Environment::new("theorem", "thm", true));
+	add("cor", Environment::new("corollary", "cor", true));
+	add("rule", Environment::new("trule", "rule", true));
+	add("fact", Environment::new("fact", "fact", true));
+	add("rem", Environment::new("remark", "", false));
+	envs
+}
+
+/// Read environments from project configuration, keyed by prefix; these
+/// are added to the default environments, and settings given for a
+/// default environment replace its defaults
+pub fn environments_from_yaml(
+	value: &Value
+) -> Result<HashMap<String, Environment>, String> {
+	let mut envs = default_environments();
+	let map = value
+		.as_mapping()
+		.ok_or("expected a map of prefixes to environments")?;
+	for (k, v) in map.iter() {
+		let prefix = k.as_str().ok_or("expected prefix of file names")?;
+		if prefix.is_empty() || prefix.contains('_') {
+			return Err(format!("invalid prefix `{}`", prefix));
+		}
+		if BUILT_IN.contains(&prefix) {
+			return Err(format!(
+				"`{}` is a built-in environment and cannot be redefined",
+				prefix
+			));
+		}
+		// New environments are named after their prefix by default
+		let base = envs
+			.get(prefix)
+			.cloned()
+			.unwrap_or_else(|| Environment::new(prefix, prefix, false));
+		let env = Environment::from_yaml(base, v)
+			.map_err(|e| format!("{} in `{}`", e, prefix))?;
+		envs.insert(prefix.to_string(), env);
+	}
+
+	// An environment can only be defined once in the preamble
+	let mut headings: HashMap<&str, &str> = HashMap::new();
+	for env in envs.values().filter(|e| !e.heading.is_empty()) {
+		match headings.insert(&env.name, &env.heading) {
+			Some(h) if h != env.heading => {
+				return Err(format!(
+					"environment `{}` has headings `{}` and `{}`",
+					env.name, h, env.heading
+				))
+			}
+			_ => (),
+		}
+	}
+	Ok(envs)
+}
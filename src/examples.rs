@@ -0,0 +1,155 @@
+use crate::graph::clean_path;
+use crate::node::Node;
+use crate::topic::Topic;
+use std::{
+	cell::RefCell,
+	collections::{HashMap, HashSet},
+	rc::Rc,
+};
+
+/// Examples of each topic, keyed by path of topic, from the `examples` key
+/// of topics and the `eg_of` key of examples, in the order they are listed
+fn examples_by_topic(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>]
+) -> HashMap<String, Vec<String>> {
+	let mut examples: HashMap<String, Vec<String>> = HashMap::new();
+	let mut add = |topic: String, example: String| {
+		let list = examples.entry(topic).or_default();
+		if !list.contains(&example) {
+			list.push(example);
+		}
+	};
+	for n in sorted_nodes.iter().rev() {
+		let node = n.borrow();
+		for e in node.data().examples.iter() {
+			add(node.path.clone(), clean_path(e));
+		}
+		for t in node.data().eg_of.iter() {
+			add(clean_path(t), node.path.clone());
+		}
+	}
+	examples
+}
+
+/// Check if every node in the document that an example requires has
+/// already been placed
+fn prerequisites_placed(
+	example: &Node<Topic>,
+	in_document: &HashMap<String, usize>,
+	placed: &HashSet<String>,
+) -> bool {
+	example.req.iter().map(|r| clean_path(r)).all(|r| {
+		r == example.path
+			|| !in_document.contains_key(&r)
+			|| placed.contains(&r)
+	})
+}
+
+/// Examples that are placed after their topics: those with a topic in the
+/// document that is not itself such an example, or that is an example
+/// placed after a topic in turn
+fn tied_examples(
+	examples: &HashMap<String, Vec<String>>,
+	in_document: &HashMap<String, usize>,
+) -> HashSet<String> {
+	let mut tied: HashSet<String> = HashSet::new();
+	let mut anchored: Vec<&String> = in_document
+		.keys()
+		.filter(|p| !examples.values().any(|e| e.contains(p)))
+		.collect();
+	while let Some(topic) = anchored.pop() {
+		for e in examples.get(topic).into_iter().flatten() {
+			if in_document.contains_key(e) && tied.insert(e.clone()) {
+				anchored.push(e);
+			}
+		}
+	}
+	tied
+}
+
+/// Move examples so that each appears directly after the topic it
+/// illustrates, regardless of how branches were sorted; an example of
+/// several topics follows the first of them in the document, and examples
+/// of topics that are not in the document stay where they are; an example
+/// that would then come before a node it requires stays in its sorted
+/// position instead, or comes as soon as possible after it
+pub fn place_examples(
+	sorted_nodes: Vec<Rc<RefCell<Node<Topic>>>>
+) -> Vec<Rc<RefCell<Node<Topic>>>> {
+	let examples = examples_by_topic(&sorted_nodes);
+
+	// Document order is the reverse of the sorted order
+	let nodes: Vec<Rc<RefCell<Node<Topic>>>> =
+		sorted_nodes.into_iter().rev().collect();
+	let in_document: HashMap<String, usize> = nodes
+		.iter()
+		.enumerate()
+		.map(|(i, n)| (n.borrow().path.clone(), i))
+		.collect();
+	let tied = tied_examples(&examples, &in_document);
+
+	let mut placed: HashSet<String> = HashSet::new();
+	let mut document: Vec<Rc<RefCell<Node<Topic>>>> = vec![];
+	let mut untied: Vec<usize> = vec![];
+	for (i, n) in nodes.iter().enumerate() {
+		let path = n.borrow().path.clone();
+		if placed.contains(&path)
+			|| (tied.contains(&path) && !untied.contains(&i))
+		{
+			continue;
+		}
+
+		// Place the node, followed by its examples and theirs in turn, and
+		// then any examples that were passed while waiting for their
+		// prerequisites
+		let mut stack = vec![i];
+		while !stack.is_empty() {
+			while let Some(j) = stack.pop() {
+				let node = nodes[j].borrow();
+				if !placed.insert(node.path.clone()) {
+					continue;
+				}
+				document.push(nodes[j].clone());
+				let mut next = vec![];
+				for e in examples.get(&node.path).into_iter().flatten() {
+					let k = match in_document.get(e) {
+						Some(k) if tied.contains(e) && !placed.contains(e) => *k,
+						_ => continue,
+					};
+					if prerequisites_placed(
+						&nodes[k].borrow(),
+						&in_document,
+						&placed,
+					) {
+						next.push(k);
+					} else if !untied.contains(&k) {
+						untied.push(k);
+					}
+				}
+				stack.extend(next.into_iter().rev());
+			}
+			stack = untied
+				.iter()
+				.filter(|k| **k < i)
+				.filter(|k| {
+					let node = nodes[**k].borrow();
+					!placed.contains(&node.path)
+						&& prerequisites_placed(&node, &in_document, &placed)
+				})
+				.rev()
+				.copied()
+				.collect();
+		}
+	}
+
+	// Anything still left out, e.g. an example of an example of itself,
+	// stays in sorted order at the end
+	for n in nodes.iter() {
+		if !placed.contains(&n.borrow().path) {
+			document.push(n.clone());
+		}
+	}
+
+	document.reverse();
+	document
+}
@@ -0,0 +1,121 @@
+use crate::node::Node;
+use crate::topic::Topic;
+use std::{cell::RefCell, fs::File, io::Write, rc::Rc};
+
+/// Title of a chapter, and exercises with solutions in it
+type SolvedChapter = (String, Vec<Rc<RefCell<Node<Topic>>>>);
+
+/// Exercises (`ex` nodes) gathered while writing a document; exercises are
+/// printed at the end of the chapter they appear in, and their solutions
+/// in an appendix at the end of the document
+#[derive(Default)]
+pub struct Exercises {
+	/// Title of the current chapter, if any
+	chapter: String,
+	/// Exercises in the current chapter
+	pending: Vec<Rc<RefCell<Node<Topic>>>>,
+	/// Exercises with solutions, grouped by chapter title
+	solved: Vec<SolvedChapter>,
+}
+
+impl Exercises {
+	/// Add an exercise to the current chapter
+	pub fn add(
+		&mut self,
+		node: Rc<RefCell<Node<Topic>>>,
+	) {
+		self.pending.push(node);
+	}
+
+	/// Start a new chapter; an empty title starts a part of the document
+	/// without a chapter title
+	pub fn start_chapter(
+		&mut self,
+		title: &str,
+	) {
+		self.chapter = title.to_string();
+	}
+
+	/// Write exercises in the current chapter under an unnumbered heading
+	/// using `heading_cmd`, e.g. `section`, and keep those with solutions
+	/// for the appendix
+	pub fn write_chapter(
+		&mut self,
+		heading_cmd: &str,
+		file: &mut File,
+	) {
+		if self.pending.is_empty() {
+			return;
+		}
+		file.write_all(b"\n\\").expect("");
+		file.write_all(heading_cmd.as_bytes()).expect("");
+		file.write_all(b"*{Exercises}\n\n").expect("");
+		for node in self.pending.iter() {
+			let node = node.borrow();
+			file.write_all(b"\\begin{exercise}[").expect("");
+			file.write_all(node.data().label.as_bytes()).expect("");
+			file.write_all(b"]\\label{ex:").expect("");
+			file.write_all(node.path.as_bytes()).expect("");
+			file.write_all(b"}\n").expect("");
+			file.write_all(node.data().main.as_bytes()).expect("");
+			file.write_all(b"\\end{exercise}\n\n").expect("");
+		}
+		let solved: Vec<Rc<RefCell<Node<Topic>>>> = self
+			.pending
+			.drain(..)
+			.filter(|n| !n.borrow().data().sol.is_empty())
+			.collect();
+		if !solved.is_empty() {
+			self.solved.push((self.chapter.clone(), solved));
+		}
+	}
+
+	/// Write solutions under a heading using `heading_cmd`, e.g.
+	/// `chapter`, grouped under unnumbered headings using `group_cmd` for
+	/// each chapter with a title; starts the appendix first if
+	/// `start_appendix` is set
+	pub fn write_solutions(
+		&self,
+		heading_cmd: &str,
+		group_cmd: &str,
+		start_appendix: bool,
+		file: &mut File,
+	) {
+		if self.solved.is_empty() {
+			return;
+		}
+		if start_appendix {
+			file.write_all(b"\n\\appendix\n").expect("");
+		}
+		file.write_all(b"\n\\").expect("");
+		file.write_all(heading_cmd.as_bytes()).expect("");
+		file.write_all(b"{Solutions}\n\n").expect("");
+		for (chapter, nodes) in self.solved.iter() {
+			if !chapter.is_empty() {
+				file.write_all(b"\\").expect("");
+				file.write_all(group_cmd.as_bytes()).expect("");
+				file.write_all(b"*{").expect("");
+				file.write_all(chapter.as_bytes()).expect("");
+				file.write_all(b"}\n\n").expect("");
+			}
+			for node in nodes.iter() {
+				let node = node.borrow();
+				file.write_all(b"\\begin{solution}{ex:").expect("");
+				file.write_all(node.path.as_bytes()).expect("");
+				file.write_all(b"}\n").expect("");
+				file.write_all(node.data().sol.as_bytes()).expect("");
+				file.write_all(b"\\end{solution}\n\n").expect("");
+			}
+		}
+	}
+}
+
+/// Command for a heading one level below `heading_cmd`, used for headings
+/// within chapters (or top-level sections) such as exercises
+pub fn subheading_cmd(heading_cmd: &str) -> &'static str {
+	match heading_cmd {
+		"chapter" => "section",
+		"section" => "subsection",
+		_ => "subsubsection",
+	}
+}
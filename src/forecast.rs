@@ -0,0 +1,160 @@
+use crate::calendar::{Calendar, WorkTime};
+use crate::node::Node;
+use crate::report::EstimationBias;
+use crate::schedule::level_tasks;
+use crate::topic::Topic;
+use chrono::NaiveDate;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{cell::RefCell, rc::Rc};
+
+/// Number of simulated outcomes
+const TRIALS: usize = 10000;
+
+/// Finish dates of a whole project
+pub struct Forecast {
+	/// Date by which all tasks are finished in 50 percent of simulated
+	/// outcomes
+	pub p50: NaiveDate,
+	/// Date by which all tasks are finished in 90 percent of simulated
+	/// outcomes
+	pub p90: NaiveDate,
+}
+
+/// Sample a duration from a triangular distribution
+fn sample_triangular(
+	rng: &mut StdRng,
+	optimistic: f64,
+	likely: f64,
+	pessimistic: f64,
+) -> f64 {
+	if pessimistic <= optimistic {
+		return optimistic;
+	}
+	let u: f64 = rng.gen();
+	let range = pessimistic - optimistic;
+	if u < (likely - optimistic) / range {
+		optimistic + (u * range * (likely - optimistic)).sqrt()
+	} else {
+		pessimistic - ((1.0 - u) * range * (pessimistic - likely)).sqrt()
+	}
+}
+
+/// Date by which a given fraction of sampled finishes are done
+fn percentile(
+	samples: &mut [WorkTime],
+	q: f64,
+) -> NaiveDate {
+	samples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+	let i = ((q * samples.len() as f64).ceil() as usize).max(1) - 1;
+	samples[i].end_date()
+}
+
+/// Simulate finishing all incomplete tasks many times, sampling each
+/// task's duration from its three-point estimate (or using its expected
+/// duration if it has none), less the part already complete, and
+/// levelling tasks as `schedule_tasks`
+/// does; sets the probability of meeting each deadline and the 50th and
+/// 90th percentile finish dates of each incomplete task; returns
+/// percentile finish dates of the whole project, or `None` if there are
+/// no incomplete tasks; if `bias` is given, durations are calibrated
+/// using the estimation bias of each task's assignees
+pub fn forecast_tasks(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>],
+	today: NaiveDate,
+	calendar: &Calendar,
+	bias: Option<&EstimationBias>,
+) -> Option<Forecast> {
+	let today = WorkTime::from_date(today);
+
+	// Seed is fixed so that documents do not change between runs
+	let mut rng = StdRng::seed_from_u64(0);
+
+	// Finish times of each open task in each trial
+	let mut samples: Vec<Vec<WorkTime>> =
+		vec![vec![]; sorted_nodes.len()];
+	let mut project: Vec<WorkTime> = vec![];
+	for _ in 0..TRIALS {
+		let projection =
+			level_tasks(sorted_nodes, today, calendar, |data| {
+				let factor = bias.map_or(1.0, |b| b.factor(&data.assgn));
+				factor
+					* match (data.optimistic, data.likely, data.pessimistic) {
+						(Some(o), Some(m), Some(p)) => {
+							sample_triangular(&mut rng, o, m, p)
+								* (1.0 - data.progress / 100.0)
+						}
+						_ => data.remaining(),
+					}
+			});
+		let mut last: Option<WorkTime> = None;
+		for (i, n) in sorted_nodes.iter().enumerate() {
+			let node = n.borrow();
+			if !node.data().is_open() {
+				continue;
+			}
+			if let Some((_, end)) = projection.get(&node.path) {
+				samples[i].push(*end);
+				last = Some(last.map_or(*end, |l| l.max(*end)));
+			}
+		}
+		if let Some(l) = last {
+			project.push(l);
+		}
+	}
+
+	for (i, n) in sorted_nodes.iter().enumerate() {
+		if samples[i].is_empty() {
+			continue;
+		}
+		let mut node = n.borrow_mut();
+		if let Some(dl) = node.data().deadline {
+			let met =
+				samples[i].iter().filter(|x| x.end_date() <= dl).count();
+			node.data_mut().deadline_probability =
+				Some(met as f64 / TRIALS as f64);
+		}
+		node.data_mut().forecast_p50 =
+			Some(percentile(&mut samples[i], 0.5));
+		node.data_mut().forecast_p90 =
+			Some(percentile(&mut samples[i], 0.9));
+	}
+
+	if project.is_empty() {
+		return None;
+	}
+	Some(Forecast {
+		p50: percentile(&mut project, 0.5),
+		p90: percentile(&mut project, 0.9),
+	})
+}
+
+/// Print the probability of meeting each deadline of an incomplete task,
+/// and percentile finish dates of the whole project
+pub fn print_forecast(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>],
+	forecast: &Option<Forecast>,
+) {
+	println!("========================================");
+	println!("Forecast ({} simulated outcomes):", TRIALS);
+	println!("PROBABILITY | DEADLINE | P50 | P90 | FILE");
+	for n in sorted_nodes.iter().rev() {
+		let node = n.borrow();
+		let data = node.data();
+		if let (Some(p), Some(dl)) =
+			(data.deadline_probability, data.deadline)
+		{
+			println!(
+				"{:.0}% | {} | {} | {} | {}",
+				p * 100.0,
+				dl,
+				data.forecast_p50.unwrap(),
+				data.forecast_p90.unwrap(),
+				node.path,
+			);
+		}
+	}
+	match forecast {
+		Some(f) => println!("Project finish: P50 {}, P90 {}", f.p50, f.p90),
+		None => println!("Project finish: all tasks complete"),
+	}
+}
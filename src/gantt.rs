@@ -0,0 +1,198 @@
+use crate::node::Node;
+use crate::topic::Topic;
+use chrono::{Duration, NaiveDate};
+use std::{
+	cell::RefCell,
+	cmp::{max, min},
+	fs::File,
+	io::Write,
+	rc::Rc,
+};
+
+/// Dates to show for a task in a Gantt chart
+struct Bar {
+	/// Name of the bar in the chart, used for drawing links
+	name: String,
+	label: String,
+	/// First day of work
+	begin: NaiveDate,
+	/// Last day of work (inclusive)
+	end: NaiveDate,
+	deadline: Option<NaiveDate>,
+	late: bool,
+	/// Milestones are shown as a diamond on the date they are reached
+	milestone: bool,
+}
+
+/// Name of a bar in a Gantt chart; TikZ node names may not contain
+/// periods
+fn bar_name(path: &str) -> String {
+	path.replace(".", "-").replace("/", "-").replace("\\", "-")
+}
+
+/// Get dates to show for a task; completed tasks use actual start and
+/// completion dates, incomplete tasks use projected dates; returns
+/// `None` if task has no dates to show
+fn task_bar(node: &Node<Topic>) -> Option<Bar> {
+	let data = node.data();
+	let (begin, end) = if data.is_complete() {
+		let s = data.start?;
+		let c = data.complete?;
+		(s, c)
+	} else {
+		(data.scheduled_start?, data.scheduled_end?)
+	};
+	Some(Bar {
+		name: bar_name(&node.path),
+		label: data.label.clone(),
+		begin,
+		// End dates are exclusive, but bars in `pgfgantt` include the last
+		// day
+		end: max(begin, end - Duration::days(1)),
+		deadline: data.deadline,
+		late: data.is_late(),
+		milestone: false,
+	})
+}
+
+/// Get date to show for a milestone; reached milestones use their
+/// completion date, other milestones their projected date, or their
+/// deadline if they have not been scheduled; returns `None` if
+/// milestone has no date to show
+fn milestone_bar(node: &Node<Topic>) -> Option<Bar> {
+	let data = node.data();
+	let date = if data.is_complete() {
+		data.complete?
+	} else {
+		data.scheduled_end.or(data.deadline)?
+	};
+	Some(Bar {
+		name: bar_name(&node.path),
+		label: data.label.clone(),
+		begin: date,
+		end: date,
+		deadline: data.deadline,
+		late: data.is_late(),
+		milestone: true,
+	})
+}
+
+/// Write a Gantt chart of tasks using the `pgfgantt` package; bars are
+/// shown in the order tasks appear in the document, deadlines are shown
+/// as milestones on the same line as their tasks, milestone nodes are
+/// shown as diamonds, and dependency relationships between tasks are
+/// shown as links
+pub fn write_gantt(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>],
+	file: &mut File,
+) {
+	let bars: Vec<Bar> = sorted_nodes
+		.iter()
+		.rev()
+		.filter_map(|x| {
+			let node = x.borrow();
+			if node.data().is_task() {
+				task_bar(&node)
+			} else if node.data().is_milestone() {
+				milestone_bar(&node)
+			} else {
+				None
+			}
+		})
+		.collect();
+	if bars.is_empty() {
+		return;
+	}
+
+	// Chart starts on first day of work or first deadline, which may have
+	// passed, and ends on last day of work or last deadline
+	let first = bars
+		.iter()
+		.map(|b| min(b.begin, b.deadline.unwrap_or(b.begin)))
+		.min()
+		.unwrap();
+	let last = bars
+		.iter()
+		.map(|b| max(b.end, b.deadline.unwrap_or(b.end)))
+		.max()
+		.unwrap();
+
+	file
+		.write_all(b"\\noindent\n\\resizebox{\\textwidth}{!}{%\n")
+		.expect("");
+	file
+		.write_all(
+			b"\\begin{ganttchart}[time slot format=isodate, vgrid, hgrid]{",
+		)
+		.expect("");
+	file.write_all(first.to_string().as_bytes()).expect("");
+	file.write_all(b"}{").expect("");
+	file.write_all(last.to_string().as_bytes()).expect("");
+	file.write_all(b"}\n").expect("");
+	file
+		.write_all(b"\\gantttitlecalendar{year, month=name} \\\\\n")
+		.expect("");
+
+	for b in bars.iter() {
+		if b.milestone {
+			file.write_all(b"\\ganttmilestone[name=").expect("");
+			file.write_all(b.name.as_bytes()).expect("");
+			if b.late {
+				file
+					.write_all(b", milestone/.append style={fill=red}")
+					.expect("");
+			}
+			file.write_all(b"]{").expect("");
+			file.write_all(b.label.as_bytes()).expect("");
+			file.write_all(b"}{").expect("");
+			file.write_all(b.begin.to_string().as_bytes()).expect("");
+			file.write_all(b"} \\\\\n").expect("");
+			continue;
+		}
+		file.write_all(b"\\ganttbar[name=").expect("");
+		file.write_all(b.name.as_bytes()).expect("");
+		if b.late {
+			file
+				.write_all(b", bar/.append style={fill=red!50}")
+				.expect("");
+		}
+		file.write_all(b"]{").expect("");
+		file.write_all(b.label.as_bytes()).expect("");
+		file.write_all(b"}{").expect("");
+		file.write_all(b.begin.to_string().as_bytes()).expect("");
+		file.write_all(b"}{").expect("");
+		file.write_all(b.end.to_string().as_bytes()).expect("");
+		file.write_all(b"}").expect("");
+
+		// Mark deadline on the same line as the task
+		if let Some(dl) = b.deadline {
+			file
+				.write_all(
+					b" \\ganttmilestone[milestone/.append style={fill=red}]{}{",
+				)
+				.expect("");
+			file.write_all(dl.to_string().as_bytes()).expect("");
+			file.write_all(b"}").expect("");
+		}
+		file.write_all(b" \\\\\n").expect("");
+	}
+
+	// Link tasks to the tasks that depend on them
+	for n in sorted_nodes.iter() {
+		let name = bar_name(&n.borrow().path);
+		if !bars.iter().any(|b| b.name == name) {
+			continue;
+		}
+		for p in n.borrow().predecessors() {
+			let pred_name = bar_name(&p.borrow().path);
+			if bars.iter().any(|b| b.name == pred_name) {
+				file.write_all(b"\\ganttlink{").expect("");
+				file.write_all(pred_name.as_bytes()).expect("");
+				file.write_all(b"}{").expect("");
+				file.write_all(name.as_bytes()).expect("");
+				file.write_all(b"}\n").expect("");
+			}
+		}
+	}
+	file.write_all(b"\\end{ganttchart}}\n\n").expect("");
+}
@@ -4,20 +4,39 @@ use std::{
 	cell::RefCell, collections::HashMap, collections::HashSet, rc::Rc,
 };
 
+/// Function that creates a node from the contents of a file
+pub type CreateNode<T, U> =
+	fn(&String, U, &Options) -> Rc<RefCell<Node<T>>>;
+
+/// Path of a node relative to the current directory, as given in a
+/// `req` or `incl` list or on the command line
+pub fn clean_path(path: &str) -> String {
+	path
+		.replace("../", "")
+		.replace("..\\", "")
+		.replace("./", "")
+		.replace(".\\", "")
+}
+
+/// Check if a node lists the node at `path` under `req`
+pub fn requires<T>(
+	node: &Node<T>,
+	path: &str,
+) -> bool {
+	node.req.iter().any(|r| clean_path(r) == path)
+}
+
 pub fn load_node<T, U>(
 	nodes: &mut HashMap<String, Rc<RefCell<Node<T>>>>,
 	path: &String,
 	read_from_file: fn(&String) -> U,
-	create_node: fn(&String, U) -> Rc<RefCell<Node<T>>>,
+	create_node: CreateNode<T, U>,
+	options: &Options,
 ) -> Rc<RefCell<Node<T>>> {
-	let clean_path = path
-		.replace("../", "")
-		.replace("..\\", "")
-		.replace("./", "")
-		.replace(".\\", "");
+	let clean_path = clean_path(path);
 	if nodes.contains_key(&clean_path) == false {
 		let dm = read_from_file(&clean_path);
-		let new_node = create_node(&clean_path, dm);
+		let new_node = create_node(&clean_path, dm, options);
 		nodes.insert(clean_path.clone(), new_node.clone());
 	}
 	nodes[&clean_path].clone()
@@ -30,19 +49,43 @@ pub fn build_dag_from_nodes<T, U>(
 	pbranch: &mut HashSet<String>,
 	sbranch: &mut HashSet<String>,
 	read_from_file: fn(&String) -> U,
-	create_node: fn(&String, U) -> Rc<RefCell<Node<T>>>,
+	create_node: CreateNode<T, U>,
 	options: &Options,
 	sdepth: i64,
 ) {
 	let node_path = node.borrow().path.clone();
 
+	// Nodes the reader already knows are satisfied; neither their
+	// prerequisites nor the nodes they include are needed
+	if options.known.contains(&node_path) {
+		return;
+	}
+
+	// Generalizations and special cases are presented in the order chosen
+	// by the user; those presented later are included like successors,
+	// and those presented earlier are required like predecessors
+	let (before, after) = {
+		let node = node.borrow();
+		if options.generalizations_before {
+			(node.gen.clone(), node.case.clone())
+		} else {
+			(node.case.clone(), node.gen.clone())
+		}
+	};
+
 	// Add successors
 	if sdepth != 0 {
 		sbranch.insert(node_path.clone());
-		let incl_list = node.borrow().incl.clone();
+		let mut incl_list = node.borrow().incl.clone();
+		incl_list.extend(after);
 		for incl_path in incl_list.iter() {
-			let incl_node =
-				load_node(nodes, incl_path, read_from_file, create_node);
+			let incl_node = load_node(
+				nodes,
+				incl_path,
+				read_from_file,
+				create_node,
+				options,
+			);
 			// These two conditions are required to guarantee termination
 			let already_in_dag =
 				incl_node.borrow().has_predecessor(node.clone());
@@ -71,10 +114,11 @@ pub fn build_dag_from_nodes<T, U>(
 
 	// Add predecessors
 	pbranch.insert(node_path.clone());
-	let req_list = node.borrow().req.clone();
+	let mut req_list = node.borrow().req.clone();
+	req_list.extend(before);
 	for req_path in req_list.iter() {
 		let req_node =
-			load_node(nodes, req_path, read_from_file, create_node);
+			load_node(nodes, req_path, read_from_file, create_node, options);
 		// These two conditions are required to guarantee termination
 		let already_in_dag =
 			node.borrow().has_predecessor(req_node.clone());
@@ -98,6 +142,53 @@ pub fn build_dag_from_nodes<T, U>(
 	pbranch.remove(&node_path);
 }
 
+/// Check if `target` is `node` or comes before it in the DAG
+fn comes_before<T>(
+	target: &Rc<RefCell<Node<T>>>,
+	node: &Rc<RefCell<Node<T>>>,
+	visited: &mut HashSet<String>,
+) -> bool {
+	if Rc::ptr_eq(target, node) {
+		return true;
+	}
+	node.borrow().predecessors().iter().any(|p| {
+		visited.insert(p.borrow().path.clone())
+			&& comes_before(target, p, visited)
+	})
+}
+
+/// Order each node before the generalizations or special cases presented
+/// after it; `build_dag_from_nodes` only adds these edges for nodes whose
+/// successors are loaded, so nodes loaded for another reason are ordered
+/// here regardless of the successor depth
+pub fn order_generalizations<T>(
+	nodes: &HashMap<String, Rc<RefCell<Node<T>>>>,
+	options: &Options,
+) {
+	for (path, node) in nodes.iter() {
+		if options.known.contains(path) {
+			continue;
+		}
+		let after = if options.generalizations_before {
+			node.borrow().case.clone()
+		} else {
+			node.borrow().gen.clone()
+		};
+		for a in after.iter().map(|a| clean_path(a)) {
+			let after_node = match nodes.get(&a) {
+				Some(n) if !options.known.contains(&a) => n,
+				_ => continue,
+			};
+			if after_node.borrow().has_predecessor(node.clone())
+				|| comes_before(after_node, node, &mut HashSet::new())
+			{
+				continue;
+			}
+			after_node.borrow_mut().add_predecessor_node(node.clone());
+		}
+	}
+}
+
 pub fn remove_indirect_predecessors<T>(node: Rc<RefCell<Node<T>>>) {
 	let mut remove = vec![];
 	for child in node.borrow().predecessors().iter() {
@@ -2,6 +2,18 @@ use crate::node::Node;
 use crate::topic::Topic;
 use std::{cell::RefCell, rc::Rc};
 
+/// Depth of chapter headings for a given maximum heading depth, or of
+/// top-level headings if the document has no chapters; 0 if there are no
+/// headings
+pub fn chapter_heading_depth(max_heading_depth: usize) -> usize {
+	match max_heading_depth {
+		0 => 0,
+		5 => 2,
+		6 => 3,
+		_ => 1,
+	}
+}
+
 /// Compute minimum cost for a node to be considered the end of a
 /// section with deepest heading level
 pub fn compute_min_dag_costs(
@@ -47,7 +59,8 @@ pub fn compute_min_dag_costs(
 }
 
 /// Set heading depth for node and all its predecessors if generating
-/// headings
+/// headings; examples and exercises never start a heading, since they are
+/// moved next to their topic or to the end of their chapter
 pub fn set_heading_depth(
 	node: Rc<RefCell<Node<Topic>>>,
 	min_cost: &usize,
@@ -56,6 +69,8 @@ pub fn set_heading_depth(
 		let possibly_parallel = {
 			p.borrow().dag_cost() > *min_cost
 				&& node.borrow().predecessors().len() > 1
+				&& p.borrow().data().env != "ex"
+				&& p.borrow().data().env != "eg"
 		};
 		if possibly_parallel {
 			p.borrow_mut().data_mut().heading_depth =
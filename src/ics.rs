@@ -0,0 +1,158 @@
+use crate::node::Node;
+use crate::topic::{Status, Topic};
+use chrono::{NaiveDate, NaiveTime};
+use std::{cell::RefCell, fs::File, io::Write, path::Path, rc::Rc};
+
+/// Escape text according to RFC 5545
+fn escape_text(text: &str) -> String {
+	text
+		.trim_end()
+		.replace("\\", "\\\\")
+		.replace(";", "\\;")
+		.replace(",", "\\,")
+		.replace("\r\n", "\\n")
+		.replace("\n", "\\n")
+}
+
+/// Write a content line, folding lines longer than 75 octets
+fn write_line(
+	file: &mut File,
+	line: &str,
+) {
+	let mut len = 0;
+	for c in line.chars() {
+		if len + c.len_utf8() > 75 {
+			file.write_all(b"\r\n ").expect("");
+			len = 1;
+		}
+		let mut buf = [0; 4];
+		file
+			.write_all(c.encode_utf8(&mut buf).as_bytes())
+			.expect("");
+		len += c.len_utf8();
+	}
+	file.write_all(b"\r\n").expect("");
+}
+
+/// Format a date as a property value; dates with a time of day are
+/// written as local date-times, other dates as dates
+fn format_date(
+	date: NaiveDate,
+	time: Option<NaiveTime>,
+) -> String {
+	match time {
+		Some(t) => {
+			format!(":{}T{}", date.format("%Y%m%d"), t.format("%H%M%S"))
+		}
+		None => format!(";VALUE=DATE:{}", date.format("%Y%m%d")),
+	}
+}
+
+/// Write an iCalendar file with one VTODO per task; deadlines are due
+/// dates, and tasks that have not started use projected start dates, if
+/// any; items are stamped with the start of `today`
+pub fn write_ics(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>],
+	today: NaiveDate,
+) {
+	let path = Path::new("../output/main.ics");
+	let mut file =
+		File::create(path).expect("could not create .ics file");
+	let timestamp = today.format("%Y%m%dT000000Z").to_string();
+
+	write_line(&mut file, "BEGIN:VCALENDAR");
+	write_line(&mut file, "VERSION:2.0");
+	write_line(&mut file, "PRODID:-//tok//Tree of Knowledge//EN");
+	for n in sorted_nodes.iter().rev() {
+		let node = n.borrow();
+		let data = node.data();
+		if !data.is_task() {
+			continue;
+		}
+		let start = data.start.or(data.scheduled_start);
+		let due = data.deadline;
+
+		// Start and due dates must both be dates or both be date-times
+		let timed = match due {
+			Some(_) => data.deadline_time.is_some(),
+			None => data.start_time.is_some(),
+		};
+		let start_time = if timed {
+			Some(
+				data
+					.start_time
+					.unwrap_or_else(|| NaiveTime::from_hms_opt(0, 0, 0).unwrap()),
+			)
+		} else {
+			None
+		};
+
+		write_line(&mut file, "BEGIN:VTODO");
+		write_line(&mut file, &format!("UID:{}@tok", node.path));
+		write_line(&mut file, &format!("DTSTAMP:{}", timestamp));
+		write_line(
+			&mut file,
+			&format!("SUMMARY:{}", escape_text(&data.label)),
+		);
+		if !data.main.is_empty() {
+			write_line(
+				&mut file,
+				&format!("DESCRIPTION:{}", escape_text(&data.main)),
+			);
+		}
+		if !data.assgn.is_empty() {
+			write_line(
+				&mut file,
+				&format!(
+					"CATEGORIES:{}",
+					data
+						.assgn
+						.iter()
+						.map(|x| escape_text(x))
+						.collect::<Vec<String>>()
+						.join(",")
+				),
+			);
+		}
+
+		// Start date must not be after due date
+		match (start, due) {
+			(Some(s), Some(d)) if s > d => (),
+			(Some(s), _) => write_line(
+				&mut file,
+				&format!("DTSTART{}", format_date(s, start_time)),
+			),
+			_ => (),
+		}
+		if let Some(d) = due {
+			write_line(
+				&mut file,
+				&format!("DUE{}", format_date(d, data.deadline_time)),
+			);
+		}
+		if data.status == Status::Cancelled {
+			write_line(&mut file, "STATUS:CANCELLED");
+		} else if data.status == Status::InProgress {
+			write_line(&mut file, "STATUS:IN-PROCESS");
+		} else if data.is_complete() {
+			write_line(&mut file, "STATUS:COMPLETED");
+			if let Some(c) = data.complete {
+				let t = data
+					.complete_time
+					.unwrap_or_else(|| NaiveTime::from_hms_opt(0, 0, 0).unwrap());
+				write_line(
+					&mut file,
+					&format!(
+						"COMPLETED:{}T{}Z",
+						c.format("%Y%m%d"),
+						t.format("%H%M%S")
+					),
+				);
+			}
+		} else {
+			write_line(&mut file, "STATUS:NEEDS-ACTION");
+		}
+		write_line(&mut file, "END:VTODO");
+	}
+	write_line(&mut file, "END:VCALENDAR");
+}
@@ -0,0 +1,412 @@
+use crate::calendar::parse_duration;
+use crate::date::parse_date;
+use crate::options::Options;
+use crate::topic::Status;
+use serde_yaml::{Mapping, Value};
+use std::{
+	collections::{HashMap, HashSet},
+	fs::File,
+	io::{prelude::*, Write},
+	path::Path,
+};
+
+/// Names of columns read from CSV files
+const CSV_COLUMNS: [&str; 24] = [
+	"id",
+	"label",
+	"title",
+	"name",
+	"task",
+	"summary",
+	"deadline",
+	"due",
+	"due date",
+	"expected",
+	"estimate",
+	"duration",
+	"assgn",
+	"assignee",
+	"assignees",
+	"owner",
+	"req",
+	"predecessors",
+	"depends on",
+	"dependencies",
+	"status",
+	"start",
+	"complete",
+	"completed",
+];
+
+/// A task read from another format, before it is written to a YAML file
+#[derive(Default)]
+struct ImportedTask {
+	/// Identifier used by other tasks to refer to this task
+	id: String,
+	/// Whether the identifier was given, rather than numbered by row or
+	/// line; numbered identifiers are only unique within a file
+	id_given: bool,
+	/// File the task was read from
+	file: String,
+	label: String,
+	deadline: String,
+	expected: String,
+	assgn: Vec<String>,
+	/// Identifiers (or labels) of prerequisites
+	req: Vec<String>,
+	status: String,
+	start: String,
+	complete: String,
+	done: bool,
+	/// Where the task was read from, for error messages
+	source: String,
+}
+
+/// Split a list of names or identifiers separated by commas or
+/// semicolons; names may contain spaces
+fn split_list(s: &str) -> Vec<String> {
+	s.split([',', ';'])
+		.map(|x| x.trim())
+		.filter(|x| !x.is_empty())
+		.map(|x| x.to_string())
+		.collect()
+}
+
+/// Read tasks from a CSV file with a header row; columns are matched by
+/// name, ignoring case, and unknown columns are ignored; tasks without an
+/// `id` column are numbered by row, starting at 1
+fn read_csv(filename: &str) -> Vec<ImportedTask> {
+	let mut reader = csv::ReaderBuilder::new()
+		.flexible(true)
+		.trim(csv::Trim::All)
+		.from_path(filename)
+		.unwrap_or_else(|e| panic!("Cannot read {}: {}", filename, e));
+	let headers: Vec<String> = reader
+		.headers()
+		.unwrap_or_else(|e| {
+			panic!("Cannot read header of {}: {}", filename, e)
+		})
+		.iter()
+		.map(|h| h.to_lowercase())
+		.collect();
+	for h in headers.iter() {
+		if !CSV_COLUMNS.contains(&h.as_str()) {
+			println!("WARNING: ignoring column `{}` in {}", h, filename);
+		}
+	}
+
+	let mut tasks = vec![];
+	for (i, record) in reader.records().enumerate() {
+		let record = record.unwrap_or_else(|e| {
+			panic!("Cannot read row {} of {}: {}", i + 2, filename, e)
+		});
+		let mut task = ImportedTask {
+			id: (i + 1).to_string(),
+			file: filename.to_string(),
+			source: format!("{} row {}", filename, i + 2),
+			..Default::default()
+		};
+		for (h, v) in headers.iter().zip(record.iter()) {
+			if v.is_empty() {
+				continue;
+			}
+			match h.as_str() {
+				"id" => {
+					task.id = v.to_string();
+					task.id_given = true;
+				}
+				"label" | "title" | "name" | "task" | "summary" => {
+					task.label = v.to_string()
+				}
+				"deadline" | "due" | "due date" => {
+					task.deadline = v.to_string()
+				}
+				"expected" | "estimate" | "duration" => {
+					task.expected = v.to_string()
+				}
+				"assgn" | "assignee" | "assignees" | "owner" => {
+					task.assgn = split_list(v)
+				}
+				"req" | "predecessors" | "depends on" | "dependencies" => {
+					task.req = split_list(v)
+				}
+				"status" => task.status = v.to_string(),
+				"start" => task.start = v.to_string(),
+				"complete" | "completed" => task.complete = v.to_string(),
+				_ => (),
+			}
+		}
+		tasks.push(task);
+	}
+	tasks
+}
+
+/// Read tasks from a todo.txt file, one task per line; completed tasks
+/// start with `x` and a completion date, contexts (`@name`) are
+/// assignees, projects (`+name`) and priorities are ignored, and the
+/// keys `id`, `due`, `est`, `dep`, and `start` give the identifier,
+/// deadline, expected duration, prerequisites, and start date
+fn read_todotxt(filename: &str) -> Vec<ImportedTask> {
+	let mut file = File::open(filename)
+		.unwrap_or_else(|e| panic!("Cannot read {}: {}", filename, e));
+	let mut contents = String::new();
+	file
+		.read_to_string(&mut contents)
+		.unwrap_or_else(|e| panic!("Cannot read {}: {}", filename, e));
+
+	let is_date =
+		|s: &str| parse_date(&Value::String(s.to_string())).is_ok();
+	let mut tasks = vec![];
+	for (i, line) in contents.lines().enumerate() {
+		if line.trim().is_empty() {
+			continue;
+		}
+		let mut task = ImportedTask {
+			id: (i + 1).to_string(),
+			file: filename.to_string(),
+			source: format!("{} line {}", filename, i + 1),
+			..Default::default()
+		};
+		let mut words = line.split_whitespace().peekable();
+
+		// Completion mark and date, priority, and creation date
+		if words.peek() == Some(&"x") {
+			words.next();
+			task.done = true;
+			if words.peek().is_some_and(|w| is_date(w)) {
+				task.complete = words.next().unwrap().to_string();
+			}
+		}
+		let is_priority =
+			|w: &&str| w.len() == 3 && w.starts_with('(') && w.ends_with(')');
+		if words.peek().is_some_and(is_priority) {
+			words.next();
+		}
+		if words.peek().is_some_and(|w| is_date(w)) {
+			words.next();
+		}
+		if words.peek().is_some_and(is_priority) {
+			words.next();
+		}
+
+		let mut label: Vec<&str> = vec![];
+		for w in words {
+			if let Some(name) = w.strip_prefix('@') {
+				task.assgn.push(name.to_string());
+			} else if w.starts_with('+') && w.len() > 1 {
+				continue;
+			} else if let Some((k, v)) = w.split_once(':') {
+				match k {
+					"id" => {
+						task.id = v.to_string();
+						task.id_given = true;
+					}
+					"due" => task.deadline = v.to_string(),
+					"est" | "expected" => task.expected = v.to_string(),
+					"dep" | "req" => task.req.extend(split_list(v)),
+					"start" => task.start = v.to_string(),
+					// Not a key, e.g. a time of day or a URL
+					_ => label.push(w),
+				}
+			} else {
+				label.push(w);
+			}
+		}
+		task.label = label.join(" ");
+		tasks.push(task);
+	}
+	tasks
+}
+
+/// Name of a YAML file for a task, e.g. `task_fix_bugs.yml`; file names
+/// are made unique by appending a number
+fn task_filename(
+	task: &ImportedTask,
+	taken: &mut HashSet<String>,
+) -> String {
+	let prefix = if task.done { "done" } else { "task" };
+	let mut slug = String::new();
+	for c in task.label.to_lowercase().chars() {
+		if c.is_alphanumeric() {
+			slug.push(c);
+		} else if !slug.ends_with('_') {
+			slug.push('_');
+		}
+	}
+	let slug = slug.trim_matches('_').to_string();
+	let slug = if slug.is_empty() {
+		task.id.clone()
+	} else {
+		slug
+	};
+	let mut filename = format!("{}_{}.yml", prefix, slug);
+	let mut n = 2;
+	while taken.contains(&filename) || Path::new(&filename).exists() {
+		filename = format!("{}_{}_{}.yml", prefix, slug, n);
+		n += 1;
+	}
+	taken.insert(filename.clone());
+	filename
+}
+
+/// Format a date for a YAML file, after checking that it is valid
+fn yaml_date(
+	task: &ImportedTask,
+	key: &str,
+	value: &str,
+) -> Value {
+	let (d, t) = parse_date(&Value::String(value.to_string()))
+		.unwrap_or_else(|e| {
+			panic!("Invalid {} in {}: {}", key, task.source, e)
+		});
+	match t {
+		Some(t) => Value::String(format!("{}T{}", d, t.format("%H:%M"))),
+		None => Value::String(d.to_string()),
+	}
+}
+
+/// Convert a task to the contents of a YAML file; prerequisites are
+/// given as file names
+fn task_yaml(
+	task: &ImportedTask,
+	req: Vec<String>,
+	hours_per_day: f64,
+) -> String {
+	let mut map = Mapping::new();
+	let mut insert = |k: &str, v: Value| {
+		map.insert(Value::String(k.to_string()), v);
+	};
+	insert("label", Value::String(task.label.clone()));
+	if !task.status.is_empty() {
+		let status = Status::parse(&task.status).unwrap_or_else(|e| {
+			panic!("Invalid status in {}: {}", task.source, e)
+		});
+		if status != Status::Done {
+			insert("status", Value::String(task.status.clone()));
+		}
+	}
+	if !task.deadline.is_empty() {
+		insert("deadline", yaml_date(task, "deadline", &task.deadline));
+	}
+	if !task.start.is_empty() {
+		insert("start", yaml_date(task, "start", &task.start));
+	}
+	if !task.complete.is_empty() {
+		insert("complete", yaml_date(task, "complete", &task.complete));
+	}
+	if !task.expected.is_empty() {
+		let value = if let Ok(days) = task.expected.parse::<u64>() {
+			Value::from(days)
+		} else if let Ok(days) = task.expected.parse::<f64>() {
+			Value::from(days)
+		} else {
+			Value::String(task.expected.clone())
+		};
+		parse_duration(&value, hours_per_day).unwrap_or_else(|e| {
+			panic!("Invalid expected duration in {}: {}", task.source, e)
+		});
+		insert("expected", value);
+	}
+	if !task.assgn.is_empty() {
+		insert(
+			"assgn",
+			Value::Sequence(
+				task
+					.assgn
+					.iter()
+					.map(|a| Value::String(a.clone()))
+					.collect(),
+			),
+		);
+	}
+	if !req.is_empty() {
+		insert(
+			"req",
+			Value::Sequence(req.into_iter().map(Value::String).collect()),
+		);
+	}
+	let yaml = serde_yaml::to_string(&map).expect("");
+	format!("{}\n", yaml.trim_start_matches("---\n").trim_end())
+}
+
+/// Write one YAML file per task read from the files given on the command
+/// line, in the current directory; tasks marked as done in the imported
+/// files get the prefix `done`, and other tasks get the prefix `task`;
+/// prerequisites are referred to by identifier, or by label
+pub fn import_tasks(options: &Options) {
+	let mut tasks = vec![];
+	for filename in options.files.iter() {
+		println!("Importing {}", filename);
+		let mut imported = match options.import_format.as_str() {
+			"csv" => read_csv(filename),
+			"todotxt" => read_todotxt(filename),
+			other => panic!(
+				"Cannot import from `{}`; expected csv or todotxt",
+				other
+			),
+		};
+		tasks.append(&mut imported);
+	}
+
+	// Tasks with a completion date, or marked as done, are complete
+	for task in tasks.iter_mut() {
+		if !task.complete.is_empty()
+			|| Status::parse(&task.status).is_ok_and(|s| s == Status::Done)
+		{
+			task.done = true;
+		}
+	}
+
+	// Choose file names first, so that prerequisites can refer to tasks
+	// later in the list
+	let mut taken: HashSet<String> = HashSet::new();
+	let filenames: Vec<String> =
+		tasks.iter().map(|t| task_filename(t, &mut taken)).collect();
+
+	// Given identifiers refer to tasks in any file, and must be unique;
+	// numbered identifiers only refer to tasks in the same file
+	let mut by_id: HashMap<&str, &str> = HashMap::new();
+	let mut by_number: HashMap<(&str, &str), &str> = HashMap::new();
+	let mut by_label: HashMap<&str, &str> = HashMap::new();
+	for (t, f) in tasks.iter().zip(filenames.iter()) {
+		by_label.entry(t.label.as_str()).or_insert(f);
+		if !t.id_given {
+			by_number.insert((t.file.as_str(), t.id.as_str()), f);
+		} else if by_id.insert(t.id.as_str(), f).is_some() {
+			panic!("Duplicate id `{}` in {}", t.id, t.source);
+		}
+	}
+
+	// Convert every task before writing any file, so that an invalid task
+	// leaves no partial import behind
+	let hours_per_day = options.config.calendar.hours_per_day;
+	let mut contents: Vec<String> = vec![];
+	for task in tasks.iter() {
+		if task.label.is_empty() {
+			panic!("Missing label in {}", task.source);
+		}
+		let req: Vec<String> = task
+			.req
+			.iter()
+			.map(|r| {
+				let r = r.as_str();
+				by_id
+					.get(r)
+					.or_else(|| by_number.get(&(task.file.as_str(), r)))
+					.or_else(|| by_label.get(r))
+					.unwrap_or_else(|| {
+						panic!("Unknown prerequisite `{}` in {}", r, task.source)
+					})
+					.to_string()
+			})
+			.collect();
+		contents.push(task_yaml(task, req, hours_per_day));
+	}
+	for (yaml, filename) in contents.iter().zip(filenames.iter()) {
+		let mut file = File::create(Path::new(filename))
+			.expect("could not create .yml file");
+		file.write_all(yaml.as_bytes()).expect("");
+		println!("Writing {}", filename);
+	}
+	println!("Imported {} tasks", tasks.len());
+}
@@ -0,0 +1,104 @@
+use crate::graph::clean_path;
+use crate::node::Node;
+use crate::tex::wiki_search_url;
+use crate::topic::Topic;
+use serde_yaml::Value;
+use std::{
+	cell::RefCell, collections::HashSet, fs::File, io::prelude::*, rc::Rc,
+};
+
+/// Read paths of nodes the reader already knows; the file may be a YAML
+/// list of paths, a saved knowledge state (a YAML map whose keys are
+/// paths, such as the state written by `tok review`, where cards not
+/// yet learned are skipped), or a text file with one path per line,
+/// where blank lines and lines starting with `#` are ignored
+pub fn read_known(filename: &str) -> HashSet<String> {
+	let mut contents = String::new();
+	File::open(filename)
+		.unwrap_or_else(|e| panic!("Cannot open {}: {}", filename, e))
+		.read_to_string(&mut contents)
+		.unwrap_or_else(|e| panic!("Cannot read {}: {}", filename, e));
+	let paths: Vec<String> = match serde_yaml::from_str(&contents) {
+		Ok(Value::Sequence(seq)) => seq
+			.iter()
+			.map(|v| match v.as_str() {
+				Some(s) => s.to_string(),
+				None => {
+					panic!("Invalid path in {}: expected a string", filename)
+				}
+			})
+			.collect(),
+		Ok(Value::Mapping(map)) => map
+			.iter()
+			.filter(|(_, v)| v.get("learned") != Some(&Value::Bool(false)))
+			.map(|(k, _)| match k.as_str() {
+				Some(s) => s.to_string(),
+				None => {
+					panic!("Invalid path in {}: expected a string", filename)
+				}
+			})
+			.collect(),
+		_ => contents
+			.lines()
+			.map(|l| l.trim())
+			.filter(|l| !l.is_empty() && !l.starts_with('#'))
+			.map(|l| l.to_string())
+			.collect(),
+	};
+	paths.iter().map(|p| clean_path(p)).collect()
+}
+
+/// Link to a topic the reader already knows; uses the node's Wikipedia
+/// page if given, or searches Wikipedia for its label unless `nowiki` is
+/// set
+fn background_link(data: &Topic) -> Option<String> {
+	if !data.wiki.is_empty() {
+		Some(data.wiki.clone())
+	} else if data.nowiki || data.is_task() || data.is_milestone() {
+		None
+	} else {
+		Some(wiki_search_url(&data.label))
+	}
+}
+
+/// Write a list of topics the reader is assumed to know, which are left
+/// out of the document, linking to each topic where possible
+pub fn write_background(
+	background: &[Rc<RefCell<Node<Topic>>>],
+	file: &mut File,
+) {
+	if background.is_empty() {
+		return;
+	}
+	file
+		.write_all(
+			b"\\noindent\\textbf{Assumed Background}\n\\begin{itemize}\n",
+		)
+		.expect("");
+	for n in background.iter().rev() {
+		let node = n.borrow();
+		let data = node.data();
+		file.write_all(b"\\item ").expect("");
+		match background_link(data) {
+			Some(url) => {
+				file.write_all(b"\\href{").expect("");
+				file.write_all(url.as_bytes()).expect("");
+				file.write_all(b"}{").expect("");
+				file.write_all(data.label.as_bytes()).expect("");
+				file.write_all(b"}").expect("");
+			}
+			None => file.write_all(data.label.as_bytes()).expect(""),
+		}
+		file.write_all(b"\n").expect("");
+	}
+	file.write_all(b"\\end{itemize}\n\n").expect("");
+}
+
+/// Print topics the reader is assumed to know
+pub fn print_background(background: &[Rc<RefCell<Node<Topic>>>]) {
+	println!("========================================");
+	println!("Assumed background (left out of document):");
+	for n in background.iter().rev() {
+		println!("{} | {}", n.borrow().path, n.borrow().data().label);
+	}
+}
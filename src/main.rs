@@ -1,18 +1,62 @@
+pub mod anki;
+pub mod board;
+pub mod burndown;
+pub mod calendar;
+pub mod config;
+pub mod date;
+pub mod deadlines;
+pub mod dot;
+pub mod environments;
+pub mod examples;
+pub mod exercises;
+pub mod forecast;
+pub mod gantt;
 pub mod graph;
 pub mod headings;
+pub mod ics;
+pub mod import;
+pub mod known;
+pub mod milestones;
 pub mod node;
 pub mod options;
+pub mod progress;
+pub mod related;
+pub mod report;
+pub mod review;
+pub mod schedule;
 pub mod tex;
 pub mod topic;
 pub mod yaml;
+use crate::anki::write_anki;
+use crate::board::write_board;
+use crate::burndown::{
+	burndown_range, compute_burndown, print_burndown, write_burndown,
+};
+use crate::date::format_day_count;
+use crate::deadlines::flag_deadlines;
+use crate::dot::write_dot;
+use crate::examples::place_examples;
+use crate::forecast::{forecast_tasks, print_forecast};
 use crate::graph::{
-	build_dag_from_nodes, remove_indirect_predecessors, topological_sort,
+	build_dag_from_nodes, clean_path, order_generalizations,
+	remove_indirect_predecessors, topological_sort,
 };
 use crate::headings::{
 	add_heading_titles_to_nodes, compute_min_dag_costs, set_heading_depth,
 };
+use crate::ics::write_ics;
+use crate::import::import_tasks;
+use crate::known::print_background;
+use crate::milestones::{derive_milestone_status, milestone_summary};
 use crate::node::Node;
 use crate::options::Options;
+use crate::progress::{print_progress, rollup_progress};
+use crate::report::{
+	compute_estimation_bias, print_estimation_bias, print_workload,
+	write_estimates,
+};
+use crate::review::review_cards;
+use crate::schedule::{print_schedule, schedule_tasks};
 use crate::tex::{compile_pdf, write_bib, write_to_tex};
 use crate::topic::{compute_ordering, create_topic, Topic};
 use crate::yaml::read_from_yaml;
@@ -45,6 +89,7 @@ fn build_graph_wrapper(
 			sdepth,
 		);
 	}
+	order_generalizations(nodes, options);
 
 	// Remove indirect predecessors to generate unique DAG and compute
 	// costs accurately
@@ -77,6 +122,13 @@ fn main() -> std::io::Result<()> {
 	let matches = App::from_yaml(command_line_options).get_matches();
 	let options = Options::new(matches.clone());
 
+	// Import tasks from other formats instead of reading YAML files
+	if options.command == "import" {
+		import_tasks(&options);
+		println!("Finished.");
+		return Ok(());
+	}
+
 	// Create root node
 	let root_path = String::from("//");
 	let root = Node::new(&root_path.clone(), Topic::new());
@@ -89,12 +141,7 @@ fn main() -> std::io::Result<()> {
 		HashMap::new();
 	nodes.insert(root.borrow().path.clone(), root.clone());
 	for filename in options.files.iter() {
-		let clean_filename = filename
-			.replace("../", "")
-			.replace("..\\", "")
-			.replace("./", "")
-			.replace(".\\", "");
-		root.borrow_mut().req.push(clean_filename.to_string());
+		root.borrow_mut().req.push(clean_path(filename));
 	}
 
 	// Load nodes and construct DAG; if nodes don't have deadlines, then
@@ -107,6 +154,37 @@ fn main() -> std::io::Result<()> {
 		options.sdepth,
 	);
 
+	// Nodes that must appear in the main body of the document; if
+	// generating a document for a single assignee, these are the tasks
+	// assigned to that person
+	let final_nodes: Vec<String> = if options.assignee.is_empty() {
+		root.borrow().req.clone()
+	} else {
+		let mut assigned: Vec<String> = nodes
+			.values()
+			.filter(|x| x.borrow().data().assgn.contains(&options.assignee))
+			.map(|x| x.borrow().path.clone())
+			.collect();
+		assigned.sort();
+		assigned
+	};
+
+	// Rebuild DAG from assigned tasks; prerequisites are added back as
+	// predecessors, and nodes that are no longer reachable are dropped
+	if !options.assignee.is_empty() {
+		for node in nodes.values() {
+			node.borrow_mut().reset();
+		}
+		root.borrow_mut().req = final_nodes.clone();
+		build_graph_wrapper(
+			root.clone(),
+			&mut nodes,
+			&options,
+			options.sdepth,
+		);
+		nodes.retain(|k, v| k == "//" || v.borrow().num_successors() > 0);
+	}
+
 	// Sort nodes while preserving dependency relationships; deadlines
 	// override branch traversal; otherwise, cost influences order of
 	// branch traversal
@@ -149,16 +227,8 @@ fn main() -> std::io::Result<()> {
 			for node in nodes.values() {
 				node.borrow_mut().reset();
 			}
-			root.borrow_mut().req.clear();
 			nodes.insert(root.borrow().path.clone(), root.clone());
-			for filename in options.files.iter() {
-				let clean_filename = filename
-					.replace("../", "")
-					.replace("..\\", "")
-					.replace("./", "")
-					.replace(".\\", "");
-				root.borrow_mut().req.push(clean_filename.to_string());
-			}
+			root.borrow_mut().req = final_nodes.clone();
 			build_graph_wrapper(
 				root.clone(),
 				&mut nodes,
@@ -173,10 +243,66 @@ fn main() -> std::io::Result<()> {
 		let mut sorted_nodes: Vec<Rc<RefCell<Node<Topic>>>> =
 			topological_sort(root.clone());
 		sorted_nodes.append(&mut dl_list);
+
+		// Only show prerequisites an assignee is still blocked on
+		if !options.assignee.is_empty() {
+			sorted_nodes.retain(|x| {
+				x.borrow().data().assgn.contains(&options.assignee)
+					|| !x.borrow().data().is_task()
+					|| x.borrow().data().is_open()
+			});
+		}
 		sorted_nodes
 	};
 	println!("Finished sorting nodes in DAG.");
 
+	// Leave out nodes the reader already knows; they still satisfy the
+	// prerequisites of other nodes
+	let (background, sorted_nodes): (Vec<_>, Vec<_>) = sorted_nodes
+		.into_iter()
+		.partition(|n| options.known.contains(&n.borrow().path));
+	let final_nodes: Vec<String> = final_nodes
+		.into_iter()
+		.filter(|x| !options.known.contains(x))
+		.collect();
+	if !background.is_empty() {
+		print_background(&background);
+	}
+
+	// Examples appear directly after the topics they illustrate
+	let sorted_nodes = place_examples(sorted_nodes);
+
+	// Milestones are reached once their prerequisites are complete
+	derive_milestone_status(&sorted_nodes, &nodes);
+
+	// Flag deadlines that cannot be met and report them to the user
+	flag_deadlines(root.clone(), options.as_of, &options.config.calendar);
+	for n in sorted_nodes.iter().rev() {
+		let node = n.borrow();
+		if node.data().deadline_is_inverted() {
+			println!(
+				"WARNING: {} has an earlier deadline than a prerequisite due {}",
+				node.path,
+				node.data().req_deadline.unwrap(),
+			);
+		}
+		if let Some(n) = node.data().overdue_days(options.as_of) {
+			println!(
+				"WARNING: {} is overdue by {}",
+				node.path,
+				format_day_count(n)
+			);
+		}
+		if node.data().deadline_is_infeasible() {
+			println!(
+				"WARNING: {} cannot be completed before its deadline; earliest \
+				 finish is {}",
+				node.path,
+				node.data().earliest_finish.unwrap(),
+			);
+		}
+	}
+
 	// Generate headings
 	if options.generate_headings == true || options.extra_headings == true
 	{
@@ -229,8 +355,51 @@ fn main() -> std::io::Result<()> {
 			n.borrow().path,
 			n.borrow().data().label,
 		);
+		if n.borrow().data().is_milestone() {
+			println!(" ==== {}", milestone_summary(&n.borrow()));
+		}
 	}
 	println!("{} total nodes", sorted_nodes.len());
+
+	// Roll up remaining work over the tasks each node depends on
+	let project_progress = rollup_progress(&sorted_nodes);
+	if options.progress {
+		print_progress(&sorted_nodes, project_progress);
+	}
+
+	// Report workload per assignee
+	if options.workload {
+		print_workload(
+			&sorted_nodes,
+			options.as_of,
+			&options.config.calendar,
+		);
+	}
+
+	// Project start and end dates of incomplete tasks
+	let bias = compute_estimation_bias(&sorted_nodes);
+	if options.schedule || options.gantt || options.ics {
+		schedule_tasks(
+			&sorted_nodes,
+			options.as_of,
+			&options.config.calendar,
+			if options.calibrate { Some(&bias) } else { None },
+		);
+	}
+	if options.schedule {
+		print_schedule(&sorted_nodes);
+	}
+
+	// Forecast finish dates of incomplete tasks
+	if options.forecast {
+		let forecast = forecast_tasks(
+			&sorted_nodes,
+			options.as_of,
+			&options.config.calendar,
+			if options.calibrate { Some(&bias) } else { None },
+		);
+		print_forecast(&sorted_nodes, &forecast);
+	}
 	let time_to_build_dag = start_time.to(PreciseTime::now());
 	println!(
 		"Time to build DAG: {} ms.",
@@ -246,6 +415,62 @@ fn main() -> std::io::Result<()> {
 		.output()
 		.expect("Could not create output/ directory");
 
+	// Export tasks instead of generating a document
+	if options.command == "export" {
+		if options.ics {
+			println!("Writing iCalendar file ...");
+			write_ics(&sorted_nodes, options.as_of);
+		}
+		if options.anki {
+			println!("Writing Anki deck ...");
+			write_anki(&sorted_nodes, max_heading_depth);
+		}
+		if options.dot {
+			println!("Writing graph ...");
+			write_dot(&sorted_nodes);
+		}
+		println!("Finished.");
+		return Ok(());
+	}
+
+	// Write a board of tasks instead of generating a document
+	if options.command == "board" {
+		println!("Writing board ...");
+		write_board(&sorted_nodes, &nodes, &options.title, options.reverse);
+		println!("Finished.");
+		return Ok(());
+	}
+
+	// Review flashcards instead of generating a document
+	if options.command == "review" {
+		review_cards(&sorted_nodes, &options);
+		println!("Finished.");
+		return Ok(());
+	}
+
+	// Report statistics instead of generating a document
+	if options.command == "stats" {
+		if options.estimates {
+			println!("Writing estimates ...");
+			write_estimates(&sorted_nodes);
+			print_estimation_bias(&bias);
+		}
+		println!("Finished.");
+		return Ok(());
+	}
+
+	// Write a report instead of generating a document
+	if options.command == "report burndown" {
+		println!("Writing burndown ...");
+		let (from, to) =
+			burndown_range(&sorted_nodes, &options, options.as_of);
+		let weeks = compute_burndown(&sorted_nodes, from, to);
+		write_burndown(&sorted_nodes, &weeks);
+		print_burndown(&weeks);
+		println!("Finished.");
+		return Ok(());
+	}
+
 	// Symlink directories for media (e.g. code listings, images, etc.)
 	if cfg!(target_os = "macos") || cfg!(target_os = "linux") {
 		// Get parent path (project root)
@@ -283,7 +508,8 @@ fn main() -> std::io::Result<()> {
 		write_to_tex(
 			&options,
 			&sorted_nodes,
-			options.files.clone(),
+			&background,
+			final_nodes.clone(),
 			max_heading_depth,
 		);
 		write_bib(&sorted_nodes);
@@ -0,0 +1,132 @@
+use crate::graph::clean_path;
+use crate::node::Node;
+use crate::topic::{Status, Topic};
+use std::{
+	cell::RefCell,
+	collections::{HashMap, HashSet},
+	rc::Rc,
+};
+
+/// Derive status of milestones from their prerequisites; a milestone is
+/// reached once all of the tasks and milestones listed under its `req`
+/// are complete, on the latest of their completion dates, if known;
+/// cancelled milestones stay cancelled
+pub fn derive_milestone_status(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>],
+	nodes: &HashMap<String, Rc<RefCell<Node<Topic>>>>,
+) {
+	// Prerequisites appear earlier in the document, so their status is
+	// derived first
+	for n in sorted_nodes.iter().rev() {
+		if !n.borrow().data().is_milestone() {
+			continue;
+		}
+		let mut reached = true;
+		let mut complete = None;
+		let mut count = (0, 0);
+		for r in n.borrow().req.iter() {
+			let p = match nodes.get(&clean_path(r)) {
+				Some(p) => p.borrow(),
+				None => continue,
+			};
+			let data = p.data();
+			if data.is_task() || data.is_milestone() {
+				count.1 += 1;
+				if data.is_complete() {
+					count.0 += 1;
+					complete = complete.max(data.complete);
+				} else if data.status != Status::Cancelled {
+					reached = false;
+				}
+			}
+		}
+		let mut node = n.borrow_mut();
+		node.data_mut().prerequisites_complete = count;
+		if node.data().status == Status::Cancelled {
+			continue;
+		}
+		if reached {
+			node.data_mut().status = Status::Done;
+			if node.data().complete.is_none() {
+				node.data_mut().complete = complete;
+			}
+		} else {
+			node.data_mut().status = Status::Todo;
+			node.data_mut().complete = None;
+		}
+	}
+}
+
+/// One-line summary of a milestone: whether it has been reached, how
+/// many of its prerequisites are complete, and when it is due
+pub fn milestone_summary(node: &Node<Topic>) -> String {
+	let data = node.data();
+	let (complete, prerequisites) = data.prerequisites_complete;
+	let mut summary = format!("MILESTONE {}: ", data.label);
+	if data.is_complete() {
+		summary.push_str("reached");
+		if let Some(c) = data.complete {
+			summary.push_str(&format!(" {}", c));
+		}
+	} else {
+		summary.push_str(&format!(
+			"{} of {} prerequisites complete",
+			complete, prerequisites
+		));
+	}
+	if let Some(dl) = data.deadline {
+		summary.push_str(&format!(", due {}", dl));
+	}
+	if !data.is_complete() {
+		if let Some(e) = data.scheduled_end {
+			summary.push_str(&format!(", projected {}", e));
+		}
+	}
+	summary
+}
+
+/// Find where to put a section heading for each milestone; a milestone
+/// heads the section containing the tasks leading up to it that are not
+/// part of an earlier milestone, so its heading goes before the first of
+/// those in the document; returns milestones keyed by the path of the
+/// node that follows each heading
+pub fn milestone_headings(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>]
+) -> HashMap<String, Rc<RefCell<Node<Topic>>>> {
+	let mut headings = HashMap::new();
+	let mut claimed: HashSet<String> = HashSet::new();
+	let position: HashMap<String, usize> = sorted_nodes
+		.iter()
+		.rev()
+		.enumerate()
+		.map(|(i, n)| (n.borrow().path.clone(), i))
+		.collect();
+	for n in sorted_nodes.iter().rev() {
+		if !n.borrow().data().is_milestone() {
+			continue;
+		}
+
+		// Gather nodes leading up to this milestone that do not belong to
+		// an earlier milestone
+		let mut section: Vec<String> = vec![];
+		let mut stack = vec![n.clone()];
+		let mut visited: HashSet<String> = HashSet::new();
+		while let Some(m) = stack.pop() {
+			let path = m.borrow().path.clone();
+			if claimed.contains(&path) || !visited.insert(path.clone()) {
+				continue;
+			}
+			if position.contains_key(&path) {
+				section.push(path);
+			}
+			for p in m.borrow().predecessors() {
+				stack.push(p);
+			}
+		}
+		if let Some(first) = section.iter().min_by_key(|p| position[*p]) {
+			headings.insert(first.clone(), n.clone());
+		}
+		claimed.extend(section);
+	}
+	headings
+}
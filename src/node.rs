@@ -26,6 +26,14 @@ pub struct Node<T> {
 	/// Sequence of file paths with node data that this node must come
 	/// before; relationship may be broken if tok detects cycles
 	pub incl: Vec<String>,
+	/// Sequence of file paths with node data that generalize this node;
+	/// these come after this node, or before it if generalizations are
+	/// presented first
+	pub gen: Vec<String>,
+	/// Sequence of file paths with node data that are special cases of
+	/// this node; these come before this node, or after it if
+	/// generalizations are presented first
+	pub case: Vec<String>,
 	/// Vector of pointers to predecessor nodes; necessary for
 	/// constructing tree; not a YAML key
 	predecessors: Vec<Rc<RefCell<Node<T>>>>,
@@ -59,6 +67,8 @@ impl<T> Node<T> {
 			successors: vec![],
 			req: vec![],
 			incl: vec![],
+			gen: vec![],
+			case: vec![],
 			num_successors: 0,
 			data: data,
 			dag_cost: 1,
@@ -1,6 +1,11 @@
+use crate::config::Config;
+use crate::known::read_known;
+use chrono::{Local, NaiveDate};
+use std::collections::HashSet;
 use titlecase::titlecase;
 
 pub struct Options {
+	pub command: String,
 	pub verbose: bool,
 	pub yaml: bool,
 	pub reverse: bool,
@@ -19,17 +24,51 @@ pub struct Options {
 	pub generate_headings: bool,
 	pub extra_headings: bool,
 	pub sdepth: i64,
+	pub assignee: String,
+	pub workload: bool,
+	pub schedule: bool,
+	pub forecast: bool,
+	pub progress: bool,
+	pub gantt: bool,
+	pub ics: bool,
+	pub anki: bool,
+	pub dot: bool,
+	pub estimates: bool,
+	pub calibrate: bool,
+	pub burndown: bool,
+	pub from: Option<NaiveDate>,
+	pub to: Option<NaiveDate>,
+	/// Paths of nodes the reader already knows
+	pub known: HashSet<String>,
+	/// Date treated as today
+	pub as_of: NaiveDate,
+	pub import_format: String,
+	/// File storing review state of flashcards
+	pub review_state: String,
 	pub title: String,
 	pub author: String,
 	pub date: String,
 	pub output: String,
 	pub engine: String,
 	pub files: Vec<String>,
+	/// Project configuration
+	pub config: Config,
 }
 
 impl Options {
 	pub fn new(matches: clap::ArgMatches) -> Self {
+		// Subcommands take their own files and options; global options
+		// are available from either set of matches; nested subcommands
+		// are joined by spaces, e.g. `report burndown`
+		let mut matches = matches;
+		let mut commands: Vec<String> = vec![];
+		while let Some(name) = matches.subcommand_name().map(String::from) {
+			matches = matches.subcommand_matches(&name).unwrap().clone();
+			commands.push(name);
+		}
+		let command = commands.join(" ");
 		Options {
+			command,
 			verbose: matches.is_present("verbose"),
 			reverse: matches.is_present("reverse"),
 			yaml: matches.is_present("yaml"),
@@ -41,7 +80,7 @@ impl Options {
 			examples: matches.is_present("examples"),
 			exercises: matches.is_present("exercises"),
 			generalizations_before: matches
-				.is_present("generalizations_before"),
+				.is_present("generalizations_first"),
 			write_appendix: !matches.is_present("write_appendix"),
 			eli5: matches.is_present("eli5"),
 			make_pdf: !matches.is_present("make_pdf"),
@@ -53,6 +92,38 @@ impl Options {
 				.unwrap_or("-1")
 				.parse::<i64>()
 				.unwrap_or(-1),
+			assignee: matches.value_of("assignee").unwrap_or("").to_string(),
+			workload: matches.is_present("workload"),
+			schedule: matches.is_present("schedule"),
+			forecast: matches.is_present("forecast"),
+			progress: matches.is_present("progress"),
+			gantt: matches.is_present("gantt"),
+			ics: matches.is_present("ics"),
+			anki: matches.is_present("anki"),
+			dot: matches.is_present("dot"),
+			estimates: matches.is_present("estimates"),
+			calibrate: matches.is_present("calibrate"),
+			burndown: matches.is_present("burndown"),
+			from: matches
+				.value_of("from")
+				.map(|d| parse_option_date("from", d)),
+			to: matches.value_of("to").map(|d| parse_option_date("to", d)),
+			known: matches
+				.value_of("known")
+				.map_or(HashSet::new(), read_known),
+			as_of: matches
+				.value_of("as_of")
+				.map_or(Local::now().naive_local().date(), |d| {
+					parse_option_date("as-of", d)
+				}),
+			review_state: matches
+				.value_of("state")
+				.unwrap_or("../review.yml")
+				.to_string(),
+			import_format: matches
+				.value_of("format")
+				.unwrap_or("")
+				.to_string(),
 			// Replace title with title from command line, or if none given
 			// and only single file is passed as input, replace title with
 			// single file's label
@@ -91,6 +162,17 @@ impl Options {
 			engine: matches.value_of("engine").unwrap_or("").to_string(),
 			output: matches.value_of("output").unwrap_or("").to_string(),
 			files: matches.values_of_lossy("FILES").unwrap(),
+			config: Config::load(),
 		}
 	}
 }
+
+/// Parse a date given on the command line in the form YYYY-MM-DD
+fn parse_option_date(
+	name: &str,
+	value: &str,
+) -> NaiveDate {
+	NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap_or_else(|_| {
+		panic!("Invalid date for --{}: expected YYYY-MM-DD", name)
+	})
+}
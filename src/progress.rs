@@ -0,0 +1,96 @@
+use crate::calendar::format_days;
+use crate::node::Node;
+use crate::topic::Topic;
+use std::{cell::RefCell, collections::HashSet, rc::Rc};
+
+/// Add the expected duration and remaining work of every task in the
+/// subgraph ending at `node` (i.e. `node` and all of its direct and
+/// indirect predecessors), counting each task once; cancelled tasks are
+/// not counted
+fn sum_subgraph(
+	node: &Rc<RefCell<Node<Topic>>>,
+	visited: &mut HashSet<String>,
+	totals: &mut (f64, f64),
+) {
+	if !visited.insert(node.borrow().path.clone()) {
+		return;
+	}
+	{
+		let n = node.borrow();
+		let data = n.data();
+		if data.is_open() || data.is_complete() {
+			totals.0 += data.expected;
+			totals.1 += data.remaining();
+		}
+	}
+	for p in node.borrow().predecessors() {
+		sum_subgraph(&p, visited, totals);
+	}
+}
+
+/// Roll up remaining work over the subgraph ending at each node, so that
+/// progress towards a node is weighted by the expected durations of the
+/// tasks it depends on; returns the sum of expected durations and
+/// remaining work of all tasks in the document
+pub fn rollup_progress(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>]
+) -> (f64, f64) {
+	for n in sorted_nodes.iter() {
+		let mut totals = (0.0, 0.0);
+		sum_subgraph(n, &mut HashSet::new(), &mut totals);
+		let mut node = n.borrow_mut();
+		node.data_mut().rollup_expected = totals.0;
+		node.data_mut().rollup_remaining = totals.1;
+	}
+	let mut project = (0.0, 0.0);
+	for n in sorted_nodes.iter() {
+		let node = n.borrow();
+		let data = node.data();
+		if data.is_open() || data.is_complete() {
+			project.0 += data.expected;
+			project.1 += data.remaining();
+		}
+	}
+	project
+}
+
+/// Percentage of expected work that is complete
+pub fn percent_complete(
+	expected: f64,
+	remaining: f64,
+) -> f64 {
+	if expected > 0.0 {
+		100.0 * (1.0 - remaining / expected)
+	} else {
+		100.0
+	}
+}
+
+/// Print progress of each task, rolled up over the tasks it depends on,
+/// and of the whole project
+pub fn print_progress(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>],
+	project: (f64, f64),
+) {
+	println!("========================================");
+	println!("Progress (weighted by expected duration):");
+	println!("COMPLETE | REMAINING DAYS | FILE");
+	for n in sorted_nodes.iter().rev() {
+		let node = n.borrow();
+		let data = node.data();
+		if !data.is_task() || data.rollup_expected == 0.0 {
+			continue;
+		}
+		println!(
+			"{:.0}% | {} | {}",
+			percent_complete(data.rollup_expected, data.rollup_remaining),
+			format_days(data.rollup_remaining),
+			node.path,
+		);
+	}
+	println!(
+		"Project: {:.0}% complete, {} days of work remaining",
+		percent_complete(project.0, project.1),
+		format_days(project.1),
+	);
+}
@@ -0,0 +1,48 @@
+use crate::graph::clean_path;
+use crate::node::Node;
+use crate::topic::Topic;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use titlecase::titlecase;
+
+/// Related topics of each node, keyed by path of node; a node is related
+/// to the topics it lists under `rel`, in the order listed, and to the
+/// nodes that list it, in document order
+pub fn related_topics(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>]
+) -> HashMap<String, Vec<String>> {
+	let mut related: HashMap<String, Vec<String>> = HashMap::new();
+	let mut add = |a: &str, b: &str| {
+		let list = related.entry(a.to_string()).or_default();
+		if a != b && !list.iter().any(|x| x == b) {
+			list.push(b.to_string());
+		}
+	};
+	for n in sorted_nodes.iter().rev() {
+		let node = n.borrow();
+		for r in node.data().rel.iter() {
+			add(&node.path, &clean_path(r));
+		}
+	}
+	for n in sorted_nodes.iter().rev() {
+		let node = n.borrow();
+		for r in node.data().rel.iter() {
+			add(&clean_path(r), &node.path);
+		}
+	}
+	related
+}
+
+/// Label of a topic that may not have been read, from its file name, e.g.
+/// `Law of Cosines` for `thm_law_of_cosines.yml`
+pub fn label_from_path(path: &str) -> String {
+	let name = path.rsplit('/').next().unwrap_or(path);
+	let name = match name.rfind('.') {
+		Some(i) => &name[..i],
+		None => name,
+	};
+	let name = match name.find('_') {
+		Some(i) => &name[i + 1..],
+		None => name,
+	};
+	titlecase(&name.replace("_", " "))
+}
@@ -0,0 +1,289 @@
+use crate::calendar::{format_days, Calendar};
+use crate::node::Node;
+use crate::topic::Topic;
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::{
+	cell::RefCell, collections::BTreeMap, fs::File, io::Write,
+	path::Path, rc::Rc,
+};
+
+/// Open tasks assigned to a single person
+struct Workload {
+	/// Number of open tasks
+	num_tasks: usize,
+	/// Sum of remaining work in open tasks (in working days)
+	remaining: f64,
+	/// Remaining work in open tasks with deadlines
+	due: Vec<(NaiveDate, f64)>,
+}
+
+/// Print a report of open tasks per assignee; an assignee is overloaded
+/// if the remaining work in the open tasks due on or before any of
+/// their deadlines add up to more days than they have working days left
+/// until that deadline
+pub fn print_workload(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>],
+	today: NaiveDate,
+	calendar: &Calendar,
+) {
+	// Gather open tasks for each assignee
+	let mut workloads: BTreeMap<String, Workload> = BTreeMap::new();
+	for n in sorted_nodes.iter() {
+		let node = n.borrow();
+		let data = node.data();
+		if !data.is_open() {
+			continue;
+		}
+		let deadline = data.deadline;
+		for assignee in data.assgn.iter() {
+			let w = workloads.entry(assignee.clone()).or_insert(Workload {
+				num_tasks: 0,
+				remaining: 0.0,
+				due: vec![],
+			});
+			w.num_tasks += 1;
+			w.remaining += data.remaining();
+			if let Some(dl) = deadline {
+				w.due.push((dl, data.remaining()));
+			}
+		}
+	}
+
+	println!("========================================");
+	println!("Workload of open tasks:");
+	println!("ASSIGNEE | OPEN TASKS | REMAINING DAYS | STATUS");
+	for (assignee, w) in workloads.iter_mut() {
+		// Find deadline with the largest shortfall of working days
+		// available
+		w.due.sort_by_key(|x| x.0);
+		let mut demand = 0.0;
+		let mut worst: Option<(NaiveDate, f64, usize, f64)> = None;
+		for (dl, expected) in w.due.iter() {
+			demand += expected;
+			let available = calendar.working_days_between(
+				today,
+				*dl,
+				std::slice::from_ref(assignee),
+			);
+			let shortfall = demand - available as f64;
+			if shortfall > worst.map(|x| x.3).unwrap_or(0.0) {
+				worst = Some((*dl, demand, available, shortfall));
+			}
+		}
+		let status = match worst {
+			Some((dl, demand, available, _)) => format!(
+				"OVERLOADED ({} days of work due by {}, {} days available)",
+				format_days(demand),
+				dl,
+				available,
+			),
+			None => "OK".to_string(),
+		};
+		println!(
+			"{} | {} | {} | {}",
+			assignee,
+			w.num_tasks,
+			format_days(w.remaining),
+			status
+		);
+	}
+}
+
+/// Expected and actual durations of a completed task
+struct Estimate {
+	path: String,
+	label: String,
+	assgn: Vec<String>,
+	complete: NaiveDate,
+	/// Expected duration (in working days)
+	expected: f64,
+	/// Actual duration (in working days)
+	actual: f64,
+}
+
+impl Estimate {
+	/// Ratio of actual to expected duration; `None` if no duration was
+	/// expected
+	fn ratio(&self) -> Option<f64> {
+		if self.expected > 0.0 {
+			Some(self.actual / self.expected)
+		} else {
+			None
+		}
+	}
+}
+
+/// Ratio of total actual duration to total expected duration of
+/// completed tasks, for the whole project and for each assignee; a
+/// ratio greater than one means tasks take longer than expected
+pub struct EstimationBias {
+	pub project: Option<f64>,
+	pub assignees: BTreeMap<String, f64>,
+}
+
+impl EstimationBias {
+	/// Factor by which to scale the expected duration of a task; uses
+	/// the average bias of the task's assignees, or the project-wide
+	/// bias if none of the assignees have completed tasks
+	pub fn factor(
+		&self,
+		assgn: &[String],
+	) -> f64 {
+		let factors: Vec<f64> = assgn
+			.iter()
+			.filter_map(|a| self.assignees.get(a).cloned())
+			.collect();
+		if factors.is_empty() {
+			self.project.unwrap_or(1.0)
+		} else {
+			factors.iter().sum::<f64>() / factors.len() as f64
+		}
+	}
+}
+
+/// Gather completed tasks with known start and completion dates
+fn gather_estimates(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>]
+) -> Vec<Estimate> {
+	let mut estimates = vec![];
+	for n in sorted_nodes.iter().rev() {
+		let node = n.borrow();
+		let data = node.data();
+		if !data.is_complete() || data.start.is_none() {
+			continue;
+		}
+		if let Some(c) = data.complete {
+			estimates.push(Estimate {
+				path: node.path.clone(),
+				label: data.label.clone(),
+				assgn: data.assgn.clone(),
+				complete: c,
+				expected: data.expected,
+				actual: data.duration,
+			});
+		}
+	}
+	estimates
+}
+
+/// Compute estimation bias from completed tasks
+pub fn compute_estimation_bias(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>]
+) -> EstimationBias {
+	let mut project = (0.0, 0.0);
+	let mut assignees: BTreeMap<String, (f64, f64)> = BTreeMap::new();
+	for e in gather_estimates(sorted_nodes).iter() {
+		if e.expected == 0.0 {
+			continue;
+		}
+		project.0 += e.actual;
+		project.1 += e.expected;
+		for a in e.assgn.iter() {
+			let totals = assignees.entry(a.clone()).or_insert((0.0, 0.0));
+			totals.0 += e.actual;
+			totals.1 += e.expected;
+		}
+	}
+	EstimationBias {
+		project: if project.1 > 0.0 {
+			Some(project.0 / project.1)
+		} else {
+			None
+		},
+		assignees: assignees
+			.into_iter()
+			.map(|(a, t)| (a, t.0 / t.1))
+			.collect(),
+	}
+}
+
+/// Print estimation bias for the whole project and for each assignee
+pub fn print_estimation_bias(bias: &EstimationBias) {
+	println!("========================================");
+	println!("Estimation bias (actual / expected duration):");
+	match bias.project {
+		Some(b) => println!("Project | {:.2}", b),
+		None => println!("Project | -"),
+	}
+	for (a, b) in bias.assignees.iter() {
+		println!("{} | {:.2}", a, b);
+	}
+}
+
+/// Expected and actual durations of a completed task as written to a
+/// JSON file; durations are rounded like those in the CSV file
+#[derive(Serialize)]
+struct EstimateRecord<'a> {
+	file: &'a str,
+	label: &'a str,
+	assignees: &'a [String],
+	complete: String,
+	expected: f64,
+	actual: f64,
+	ratio: Option<f64>,
+}
+
+/// Round a number to a given number of decimal places
+fn round_to(
+	x: f64,
+	places: i32,
+) -> f64 {
+	let scale = 10f64.powi(places);
+	(x * scale).round() / scale
+}
+
+/// Write expected and actual durations of completed tasks to CSV and
+/// JSON files
+pub fn write_estimates(sorted_nodes: &[Rc<RefCell<Node<Topic>>>]) {
+	let estimates = gather_estimates(sorted_nodes);
+
+	// Write CSV file
+	let path = Path::new("../output/estimates.csv");
+	let mut writer =
+		csv::Writer::from_path(path).expect("could not create .csv file");
+	writer
+		.write_record([
+			"file",
+			"label",
+			"assignees",
+			"complete",
+			"expected",
+			"actual",
+			"ratio",
+		])
+		.expect("");
+	for e in estimates.iter() {
+		writer
+			.write_record([
+				e.path.clone(),
+				e.label.clone(),
+				e.assgn.join("; "),
+				e.complete.to_string(),
+				format_days(e.expected),
+				format_days(e.actual),
+				e.ratio().map_or("".to_string(), |r| format!("{:.3}", r)),
+			])
+			.expect("");
+	}
+	writer.flush().expect("");
+
+	// Write JSON file
+	let records: Vec<EstimateRecord> = estimates
+		.iter()
+		.map(|e| EstimateRecord {
+			file: &e.path,
+			label: &e.label,
+			assignees: &e.assgn,
+			complete: e.complete.to_string(),
+			expected: round_to(e.expected, 2),
+			actual: round_to(e.actual, 2),
+			ratio: e.ratio().map(|r| round_to(r, 3)),
+		})
+		.collect();
+	let path = Path::new("../output/estimates.json");
+	let mut file =
+		File::create(path).expect("could not create .json file");
+	serde_json::to_writer_pretty(&mut file, &records).expect("");
+	file.write_all(b"\n").expect("");
+}
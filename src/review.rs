@@ -0,0 +1,256 @@
+use crate::graph::requires;
+use crate::node::Node;
+use crate::options::Options;
+use crate::topic::Topic;
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::{
+	cell::RefCell,
+	collections::{BTreeMap, HashSet},
+	fs::File,
+	io::{self, prelude::*, BufRead},
+	path::Path,
+	rc::Rc,
+};
+
+/// Maximum number of cards introduced in one review session
+const NEW_CARDS_PER_SESSION: usize = 10;
+
+/// Review state of a flashcard, scheduled using the SM-2 algorithm
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Card {
+	/// Whether the card has been recalled correctly at least once
+	pub learned: bool,
+	/// Number of consecutive correct reviews
+	pub repetitions: u32,
+	/// Days between the last review and the next
+	pub interval: i64,
+	/// How easy the card is to recall; at least 1.3
+	pub easiness: f64,
+	/// Date of next review
+	pub due: NaiveDate,
+}
+
+impl Card {
+	/// Card that has not been reviewed yet
+	fn new(today: NaiveDate) -> Card {
+		Card {
+			learned: false,
+			repetitions: 0,
+			interval: 0,
+			easiness: 2.5,
+			due: today,
+		}
+	}
+
+	/// Update schedule after a review graded from 0 (no recall) to 5
+	/// (perfect recall); grades of 3 or more are correct
+	fn grade(
+		&mut self,
+		quality: u32,
+		today: NaiveDate,
+	) {
+		let q = quality.min(5) as f64;
+		if quality >= 3 {
+			self.interval = match self.repetitions {
+				0 => 1,
+				1 => 6,
+				_ => (self.interval as f64 * self.easiness).round() as i64,
+			};
+			self.repetitions += 1;
+			self.learned = true;
+		} else {
+			self.repetitions = 0;
+			self.interval = 1;
+		}
+		let easiness = (self.easiness
+			+ (0.1 - (5.0 - q) * (0.08 + (5.0 - q) * 0.02)))
+			.max(1.3);
+
+		// Round so that state file stays readable
+		self.easiness = (easiness * 100.0).round() / 100.0;
+		self.due = today + Duration::days(self.interval);
+	}
+}
+
+/// Check if a node can be made into a flashcard
+fn is_card(data: &Topic) -> bool {
+	matches!(data.env.as_str(), "def" | "thm" | "lem" | "cor")
+}
+
+/// Name of the environment of a flashcard, shown on its front
+fn card_kind(env: &str) -> &'static str {
+	match env {
+		"def" => "Definition",
+		"thm" => "Theorem",
+		"lem" => "Lemma",
+		"cor" => "Corollary",
+		_ => "",
+	}
+}
+
+/// Read review state, keyed by path of node, or start with no cards if
+/// there is no state file
+pub fn read_state(filename: &str) -> BTreeMap<String, Card> {
+	if !Path::new(filename).is_file() {
+		return BTreeMap::new();
+	}
+	let mut contents = String::new();
+	File::open(filename)
+		.unwrap_or_else(|e| panic!("Cannot open {}: {}", filename, e))
+		.read_to_string(&mut contents)
+		.unwrap_or_else(|e| panic!("Cannot read {}: {}", filename, e));
+	serde_yaml::from_str(&contents).unwrap_or_else(|e| {
+		panic!("Invalid review state in {}: {}", filename, e)
+	})
+}
+
+/// Write review state
+fn write_state(
+	filename: &str,
+	state: &BTreeMap<String, Card>,
+) {
+	let yaml = serde_yaml::to_string(state).expect("");
+	let mut file = File::create(Path::new(filename))
+		.unwrap_or_else(|e| panic!("Cannot write {}: {}", filename, e));
+	file.write_all(yaml.trim_end().as_bytes()).expect("");
+	file.write_all(b"\n").expect("");
+}
+
+/// Check if all cards a node requires have been learned; nodes that are
+/// not cards are looked through to the cards they require, and nodes the
+/// reader already knows count as learned
+fn prerequisites_learned(
+	node: &Rc<RefCell<Node<Topic>>>,
+	state: &BTreeMap<String, Card>,
+	known: &HashSet<String>,
+	visited: &mut HashSet<String>,
+) -> bool {
+	let node = node.borrow();
+	for p in node.predecessors() {
+		let path = p.borrow().path.clone();
+		if !requires(&node, &path)
+			|| known.contains(&path)
+			|| !visited.insert(path.clone())
+		{
+			continue;
+		}
+		let learned = if is_card(p.borrow().data()) {
+			state.get(&path).is_some_and(|c| c.learned)
+		} else {
+			prerequisites_learned(&p, state, known, visited)
+		};
+		if !learned {
+			return false;
+		}
+	}
+	true
+}
+
+/// Show the front of a card, wait for the reader, show the back, and
+/// read a grade from 0 to 5; returns `None` if the reader quits
+fn ask(
+	node: &Node<Topic>,
+	lines: &mut impl Iterator<Item = io::Result<String>>,
+) -> Option<u32> {
+	let data = node.data();
+	println!("========================================");
+	println!("{}: {}", card_kind(&data.env), data.label);
+	println!("(press Enter to show answer, q to quit)");
+	let line = lines.next()?.ok()?;
+	if line.trim() == "q" {
+		return None;
+	}
+	println!("----------------------------------------");
+	println!("{}", data.main.trim());
+	loop {
+		println!("Grade from 0 (forgot) to 5 (perfect), q to quit:");
+		let line = lines.next()?.ok()?;
+		match line.trim() {
+			"q" => return None,
+			s => match s.parse::<u32>() {
+				Ok(q) if q <= 5 => return Some(q),
+				_ => println!("Invalid grade `{}`", s),
+			},
+		}
+	}
+}
+
+/// Review flashcards made from definitions, theorems, lemmas, and
+/// corollaries, showing the label on the front and the main text on the
+/// back; cards due for review come first, then new cards in the order
+/// they appear in the document, each introduced only once all cards it
+/// requires have been learned; state is saved after each card
+pub fn review_cards(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>],
+	options: &Options,
+) {
+	let today = options.as_of;
+	let mut state = read_state(&options.review_state);
+	let cards: Vec<Rc<RefCell<Node<Topic>>>> = sorted_nodes
+		.iter()
+		.rev()
+		.filter(|n| is_card(n.borrow().data()))
+		.cloned()
+		.collect();
+
+	let due: Vec<Rc<RefCell<Node<Topic>>>> = cards
+		.iter()
+		.filter(|n| {
+			state.get(&n.borrow().path).is_some_and(|c| c.due <= today)
+		})
+		.cloned()
+		.collect();
+	println!("{} cards, {} due for review", cards.len(), due.len());
+
+	let stdin = io::stdin();
+	let mut lines = stdin.lock().lines();
+	let mut reviewed = 0;
+	let mut introduced = 0;
+	let mut queue = due.into_iter();
+	loop {
+		// Review due cards, then introduce new cards whose prerequisites
+		// have been learned, including those learned in this session
+		let next = queue.next().or_else(|| {
+			if introduced >= NEW_CARDS_PER_SESSION {
+				return None;
+			}
+			cards
+				.iter()
+				.find(|n| {
+					!state.contains_key(&n.borrow().path)
+						&& prerequisites_learned(
+							n,
+							&state,
+							&options.known,
+							&mut HashSet::new(),
+						)
+				})
+				.cloned()
+		});
+		let node = match next {
+			Some(n) => n,
+			None => break,
+		};
+		let path = node.borrow().path.clone();
+		let new = !state.contains_key(&path);
+		let quality = match ask(&node.borrow(), &mut lines) {
+			Some(q) => q,
+			None => break,
+		};
+		state
+			.entry(path)
+			.or_insert_with(|| Card::new(today))
+			.grade(quality, today);
+		write_state(&options.review_state, &state);
+		reviewed += 1;
+		if new {
+			introduced += 1;
+		}
+	}
+	println!("========================================");
+	println!("Reviewed {} cards ({} new)", reviewed, introduced);
+	if reviewed > 0 {
+		println!("Saved review state to {}", options.review_state);
+	}
+}
@@ -0,0 +1,178 @@
+use crate::calendar::{Calendar, WorkTime};
+use crate::milestones::milestone_summary;
+use crate::node::Node;
+use crate::report::EstimationBias;
+use crate::topic::Topic;
+use chrono::NaiveDate;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+/// Points in working time when each incomplete task starts and
+/// finishes, keyed by path; milestones start and finish when they are
+/// projected to be reached
+pub type Projection = HashMap<String, (WorkTime, WorkTime)>;
+
+/// Project when incomplete tasks start and finish, taking the remaining
+/// work in each task (in working days) from `duration`, which is called
+/// once per task; tasks are levelled in the order they appear in the
+/// document, so that deadlines and dependency relationships are
+/// respected; a task starts once all of its predecessors are finished
+/// and all of its assignees are free, since each assignee can only work
+/// on one task at a time, and only progresses on working days of the
+/// calendar
+pub fn level_tasks(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>],
+	today: WorkTime,
+	calendar: &Calendar,
+	mut duration: impl FnMut(&Topic) -> f64,
+) -> Projection {
+	let mut projection: Projection = HashMap::new();
+
+	// Points in working time when each node is finished and each assignee
+	// is free
+	let mut finished: HashMap<String, WorkTime> = HashMap::new();
+	let mut available: HashMap<String, WorkTime> = HashMap::new();
+
+	// Tasks already started keep their assignees busy regardless of
+	// where they appear in the document; they cannot finish before today,
+	// and the remaining work in a task with recorded progress starts today
+	for n in sorted_nodes.iter() {
+		let node = n.borrow();
+		let data = node.data();
+		if !data.is_open() {
+			continue;
+		}
+		if let Some(s) = data.start {
+			let begin = WorkTime::from_date(s);
+			let from = if data.progress > 0.0 { today } else { begin };
+			let end = calendar
+				.advance(from, duration(data), &data.assgn)
+				.max(today);
+			for a in data.assgn.iter() {
+				let free = available.entry(a.clone()).or_insert(end);
+				*free = free.max(end);
+			}
+			finished.insert(node.path.clone(), end);
+			projection.insert(node.path.clone(), (begin, end));
+		}
+	}
+
+	for n in sorted_nodes.iter().rev() {
+		let node = n.borrow();
+		let data = node.data();
+
+		// Completed tasks finish on their completion date, if known
+		if data.is_complete() {
+			if let Some(c) = data.complete {
+				finished.insert(node.path.clone(), WorkTime::from_date(c));
+			}
+			continue;
+		}
+
+		// Tasks already started have been levelled
+		if projection.contains_key(&node.path) {
+			continue;
+		}
+
+		// Predecessors that have not been levelled (e.g. due to a cycle)
+		// do not constrain the start date
+		let mut earliest = today;
+		for p in node.predecessors() {
+			if let Some(f) = finished.get(&p.borrow().path) {
+				earliest = earliest.max(*f);
+			}
+		}
+
+		// Only open tasks take time and occupy assignees; other nodes (and
+		// cancelled tasks) are finished as soon as their predecessors are
+		// finished, which is when milestones are projected to be reached
+		if !data.is_open() {
+			finished.insert(node.path.clone(), earliest);
+			if data.is_milestone() {
+				projection.insert(node.path.clone(), (earliest, earliest));
+			}
+			continue;
+		}
+
+		let mut begin = earliest;
+		for a in data.assgn.iter() {
+			if let Some(f) = available.get(a) {
+				begin = begin.max(*f);
+			}
+		}
+		let begin = calendar.next_working(begin, &data.assgn);
+		let end = calendar.advance(begin, duration(data), &data.assgn);
+		for a in data.assgn.iter() {
+			available.insert(a.clone(), end);
+		}
+		finished.insert(node.path.clone(), end);
+		projection.insert(node.path.clone(), (begin, end));
+	}
+	projection
+}
+
+/// Assign projected start and end dates to incomplete tasks using
+/// `level_tasks` and the remaining work in each task; if `bias` is
+/// given, remaining work is calibrated using the estimation bias of each
+/// task's assignees
+pub fn schedule_tasks(
+	sorted_nodes: &[Rc<RefCell<Node<Topic>>>],
+	today: NaiveDate,
+	calendar: &Calendar,
+	bias: Option<&EstimationBias>,
+) {
+	let projection = level_tasks(
+		sorted_nodes,
+		WorkTime::from_date(today),
+		calendar,
+		|data| {
+			data.remaining() * bias.map_or(1.0, |b| b.factor(&data.assgn))
+		},
+	);
+	for n in sorted_nodes.iter() {
+		let mut node = n.borrow_mut();
+		let (begin, end) = match projection.get(&node.path) {
+			Some(x) => *x,
+			None => continue,
+		};
+		// Milestones are reached at the end of the day
+		let start = if node.data().is_milestone() {
+			begin.end_date()
+		} else {
+			begin.date
+		};
+		node.data_mut().scheduled_start = Some(start);
+		node.data_mut().scheduled_end = Some(end.end_date());
+	}
+}
+
+/// Print projected start and end dates of incomplete tasks, flagging
+/// tasks projected to finish after their deadlines, and a summary of
+/// each incomplete milestone
+pub fn print_schedule(sorted_nodes: &[Rc<RefCell<Node<Topic>>>]) {
+	println!("========================================");
+	println!("Projected schedule:");
+	println!("START | END | DEADLINE | ASSIGNEES | FILE");
+	for n in sorted_nodes.iter().rev() {
+		let node = n.borrow();
+		let data = node.data();
+		if let (Some(s), Some(e)) =
+			(data.scheduled_start, data.scheduled_end)
+		{
+			if data.is_milestone() {
+				println!(" ==== {}", milestone_summary(&node));
+				continue;
+			}
+			let deadline =
+				data.deadline.map_or("-".to_string(), |d| d.to_string());
+			println!(
+				"{} | {} | {} | {} | {}{}",
+				s,
+				e,
+				deadline,
+				data.assgn.join(", "),
+				node.path,
+				if data.is_late() { " (LATE)" } else { "" },
+			);
+		}
+	}
+}
@@ -1,7 +1,24 @@
-use crate::{node::Node, options::Options, topic::Topic};
+use crate::{
+	burndown::{burndown_range, compute_burndown, write_burndown_chart},
+	calendar::format_days,
+	date::{format_date, format_day_count},
+	environments::Environment,
+	exercises::{subheading_cmd, Exercises},
+	gantt::write_gantt,
+	headings::chapter_heading_depth,
+	known::write_background,
+	milestones::milestone_headings,
+	node::Node,
+	options::Options,
+	progress::percent_complete,
+	related::{label_from_path, related_topics},
+	topic::{Status, Topic},
+};
+use chrono::NaiveDate;
 use std::{
 	cell::RefCell,
 	cmp::max,
+	collections::HashMap,
 	fs::File,
 	io::{prelude::*, Write},
 	path::Path,
@@ -9,6 +26,32 @@ use std::{
 	rc::Rc,
 };
 
+fn print_repeat(
+	node: Rc<RefCell<Node<Topic>>>,
+	file: &mut File,
+) {
+	// Show interval and number of completed occurrences of a repeating
+	// task
+	if let Some(repeat) = node.borrow().data().repeat {
+		file.write_all(b"\\noindent\\textbf{Repeats:} ").expect("");
+		file.write_all(repeat.describe().as_bytes()).expect("");
+		if let Some(u) = node.borrow().data().until {
+			file.write_all(b" until ").expect("");
+			file.write_all(u.to_string().as_bytes()).expect("");
+		}
+		let completed = node
+			.borrow()
+			.data()
+			.occurrences
+			.iter()
+			.filter(|x| x.1)
+			.count();
+		file
+			.write_all(format!(" ({} completed)\n\n", completed).as_bytes())
+			.expect("");
+	}
+}
+
 fn print_assignees(
 	node: Rc<RefCell<Node<Topic>>>,
 	file: &mut File,
@@ -41,33 +84,105 @@ fn print_assignees(
 	file.write_all(b"\n\n").expect("");
 }
 
-// TODO: Show time remaining before deadline
+fn print_status(
+	node: Rc<RefCell<Node<Topic>>>,
+	file: &mut File,
+) {
+	// Each state has its own symbol in the margin
+	let symbol = match node.borrow().data().status {
+		Status::Todo => "$\\square$",
+		Status::InProgress => "$\\boxdot$",
+		Status::Blocked => "$\\boxtimes$",
+		Status::Waiting => "$\\circlearrowright$",
+		Status::Cancelled => "\\ding{55}",
+		Status::Done => "\\ding{51}",
+	};
+	file.write_all(b"\\marginpar{").expect("");
+	file.write_all(symbol.as_bytes()).expect("");
+	file.write_all(b" \\textbf{").expect("");
+	file
+		.write_all(node.borrow().data().status.name().as_bytes())
+		.expect("");
+	file.write_all(b"}}\n").expect("");
+	file.write_all(b"\\reversemarginpar\n\n").expect("");
+}
+
 fn print_deadline(
 	node: Rc<RefCell<Node<Topic>>>,
+	today: NaiveDate,
 	file: &mut File,
 ) {
 	// Show deadline
 	if node.borrow().data().deadline.is_some() {
 		file.write_all(b"\\noindent").expect("");
-		let s: Vec<String> = node
-			.borrow()
-			.data()
-			.deadline
-			.clone()
-			.unwrap()
-			.iter()
-			.map(|x| {
-				if x < &10 {
-					let mut s = 0.to_string();
-					s.push_str(&x.to_string());
-					s
-				} else {
-					x.to_string()
-				}
-			})
-			.collect();
+		let s = format_date(
+			node.borrow().data().deadline.unwrap(),
+			node.borrow().data().deadline_time,
+		);
 		file.write_all(b"\\textbf{Deadline:} ").expect("");
-		file.write_all(s.join(&"-"[..]).as_bytes()).expect("");
+
+		// Show deadline in red if it cannot be met
+		let inverted = node.borrow().data().deadline_is_inverted();
+		let infeasible = node.borrow().data().deadline_is_infeasible();
+		if inverted || infeasible {
+			file.write_all(b"\\textcolor{red}{").expect("");
+		}
+		file.write_all(s.as_bytes()).expect("");
+		if inverted {
+			file.write_all(b" (prerequisite due ").expect("");
+			file
+				.write_all(
+					node
+						.borrow()
+						.data()
+						.req_deadline
+						.unwrap()
+						.to_string()
+						.as_bytes(),
+				)
+				.expect("");
+			file.write_all(b")").expect("");
+		}
+		if infeasible {
+			file.write_all(b" (earliest finish ").expect("");
+			file
+				.write_all(
+					node
+						.borrow()
+						.data()
+						.earliest_finish
+						.unwrap()
+						.to_string()
+						.as_bytes(),
+				)
+				.expect("");
+			file.write_all(b")").expect("");
+		}
+		if inverted || infeasible {
+			file.write_all(b"}").expect("");
+		}
+
+		// Show time remaining before deadline, or how long ago it passed,
+		// if it has yet to be met
+		if node.borrow().data().is_pending() {
+			let dl = node.borrow().data().deadline.unwrap();
+			let remaining = match node.borrow().data().overdue_days(today) {
+				Some(n) => {
+					format!(
+						" \\textcolor{{red}}{{(overdue by {})}}",
+						format_day_count(n)
+					)
+				}
+				None if dl == today => " (due today)".to_string(),
+				None => {
+					format!(
+						" ({} remaining)",
+						format_day_count((dl - today).num_days())
+					)
+				}
+			};
+			file.write_all(remaining.as_bytes()).expect("");
+		}
 		file.write_all(b"\n\n").expect("");
 	}
 }
@@ -85,25 +200,12 @@ fn print_start_end_dates(
 
 	// Show start date if present
 	if node.borrow().data().start.is_some() {
-		let s: Vec<String> = node
-			.borrow()
-			.data()
-			.start
-			.clone()
-			.unwrap()
-			.iter()
-			.map(|x| {
-				if x < &10 {
-					let mut s = 0.to_string();
-					s.push_str(&x.to_string());
-					s
-				} else {
-					x.to_string()
-				}
-			})
-			.collect();
+		let s = format_date(
+			node.borrow().data().start.unwrap(),
+			node.borrow().data().start_time,
+		);
 		file.write_all(b"\\textbf{Begin:} ").expect("");
-		file.write_all(s.join(&"-"[..]).as_bytes()).expect("");
+		file.write_all(s.as_bytes()).expect("");
 		if node.borrow().data().complete.is_some() {
 			file.write_all(b", ").expect("");
 		}
@@ -111,39 +213,203 @@ fn print_start_end_dates(
 
 	// Show completion date if present
 	if node.borrow().data().complete.is_some() {
-		let s: Vec<String> = node
-			.borrow()
-			.data()
-			.complete
-			.clone()
-			.unwrap()
-			.iter()
-			.map(|x| {
-				if x < &10 {
-					let mut s = 0.to_string();
-					s.push_str(&x.to_string());
-					s
-				} else {
-					x.to_string()
-				}
-			})
-			.collect();
+		let s = format_date(
+			node.borrow().data().complete.unwrap(),
+			node.borrow().data().complete_time,
+		);
 		file.write_all(b"\\textbf{End:} ").expect("");
-		file.write_all(s.join(&"-"[..]).as_bytes()).expect("");
+		file.write_all(s.as_bytes()).expect("");
 	}
 
 	// Show actual duration
 	// NOTE: Expect duration to be > 0 iff start and comple are defined
-	if node.borrow().data().duration > 0 {
+	if node.borrow().data().duration > 0.0 {
 		file.write_all(b", \\textbf{Actual Duration:} ").expect("");
 		file
-			.write_all(node.borrow().data().duration.to_string().as_bytes())
+			.write_all(format_days(node.borrow().data().duration).as_bytes())
 			.expect("");
 		file.write_all(b" days").expect("");
 	}
 	file.write_all(b"\n\n").expect("");
 }
 
+fn print_three_point_estimate(
+	node: Rc<RefCell<Node<Topic>>>,
+	file: &mut File,
+) {
+	let optimistic = node.borrow().data().optimistic;
+	let likely = node.borrow().data().likely;
+	let pessimistic = node.borrow().data().pessimistic;
+	if let (Some(o), Some(m), Some(p)) = (optimistic, likely, pessimistic)
+	{
+		file
+			.write_all(
+				format!(
+					" (optimistic {}, likely {}, pessimistic {})",
+					format_days(o),
+					format_days(m),
+					format_days(p)
+				)
+				.as_bytes(),
+			)
+			.expect("");
+	}
+}
+
+fn print_forecast(
+	node: Rc<RefCell<Node<Topic>>>,
+	file: &mut File,
+) {
+	// Show forecast if task has been simulated
+	let p50 = node.borrow().data().forecast_p50;
+	let p90 = node.borrow().data().forecast_p90;
+	if let (Some(a), Some(b)) = (p50, p90) {
+		file
+			.write_all(b"\\noindent\\textbf{Forecast:} 50\\% by ")
+			.expect("");
+		file.write_all(a.to_string().as_bytes()).expect("");
+		file.write_all(b", 90\\% by ").expect("");
+		file.write_all(b.to_string().as_bytes()).expect("");
+
+		// Show probability of meeting deadline in red if less than even
+		if let Some(p) = node.borrow().data().deadline_probability {
+			let s = format!("{:.0}\\% chance of meeting deadline", p * 100.0);
+			file.write_all(b"; ").expect("");
+			if p < 0.5 {
+				file.write_all(b"\\textcolor{red}{").expect("");
+				file.write_all(s.as_bytes()).expect("");
+				file.write_all(b"}").expect("");
+			} else {
+				file.write_all(s.as_bytes()).expect("");
+			}
+		}
+		file.write_all(b"\n\n").expect("");
+	}
+}
+
+fn print_progress(
+	node: Rc<RefCell<Node<Topic>>>,
+	file: &mut File,
+) {
+	// Draw progress bar
+	let progress = node.borrow().data().progress;
+	file.write_all(b"\\noindent\\textbf{Progress:} ").expect("");
+	file
+		.write_all(
+			format!(
+				"\\begin{{tikzpicture}}[baseline=-0.5ex]\n\
+				 \\fill[green!60!black] (0,-0.1) rectangle ({:.2},0.1);\n\
+				 \\draw (0,-0.1) rectangle (4,0.1);\n\
+				 \\end{{tikzpicture}} {:.0}\\%\n\n",
+				4.0 * progress / 100.0,
+				progress
+			)
+			.as_bytes(),
+		)
+		.expect("");
+
+	// Show progress including prerequisites if this task depends on
+	// other tasks
+	let expected = node.borrow().data().expected;
+	let rollup_expected = node.borrow().data().rollup_expected;
+	let rollup_remaining = node.borrow().data().rollup_remaining;
+	if rollup_expected > expected {
+		file
+			.write_all(
+				format!(
+					"\\noindent\\textbf{{Including Prerequisites:}} {:.0}\\% \
+					 complete, {} days remaining\n\n",
+					percent_complete(rollup_expected, rollup_remaining),
+					format_days(rollup_remaining)
+				)
+				.as_bytes(),
+			)
+			.expect("");
+	}
+
+	// Show log entries
+	if !node.borrow().data().log.is_empty() {
+		file
+			.write_all(b"\\noindent\\textbf{Log:}\n\\begin{description}\n")
+			.expect("");
+		for (d, t, note) in node.borrow().data().log.iter() {
+			file.write_all(b"\\item[").expect("");
+			file.write_all(format_date(*d, *t).as_bytes()).expect("");
+			file.write_all(b"] ").expect("");
+			file.write_all(note.as_bytes()).expect("");
+			file.write_all(b"\n").expect("");
+		}
+		file.write_all(b"\\end{description}\n\n").expect("");
+	}
+}
+
+fn print_projected_dates(
+	node: Rc<RefCell<Node<Topic>>>,
+	file: &mut File,
+) {
+	// Show projected dates if task has been scheduled
+	let start = node.borrow().data().scheduled_start;
+	let end = node.borrow().data().scheduled_end;
+	if let (Some(s), Some(e)) = (start, end) {
+		file
+			.write_all(b"\\noindent\\textbf{Projected:} ")
+			.expect("");
+		file.write_all(s.to_string().as_bytes()).expect("");
+		file.write_all(b" to ").expect("");
+
+		// Show projected end date in red if task will finish late
+		if node.borrow().data().is_late() {
+			file.write_all(b"\\textcolor{red}{").expect("");
+			file.write_all(e.to_string().as_bytes()).expect("");
+			file.write_all(b" (late)}").expect("");
+		} else {
+			file.write_all(e.to_string().as_bytes()).expect("");
+		}
+		file.write_all(b"\n\n").expect("");
+	}
+}
+
+fn print_milestone(
+	node: Rc<RefCell<Node<Topic>>>,
+	file: &mut File,
+) {
+	// Show when milestone was reached, or how far along it is
+	if node.borrow().data().is_complete() {
+		if let Some(c) = node.borrow().data().complete {
+			file.write_all(b"\\noindent\\textbf{Reached:} ").expect("");
+			file.write_all(c.to_string().as_bytes()).expect("");
+			file.write_all(b"\n\n").expect("");
+		}
+	} else {
+		let (complete, prerequisites) =
+			node.borrow().data().prerequisites_complete;
+		file
+			.write_all(b"\\noindent\\textbf{Prerequisites Complete:} ")
+			.expect("");
+		file
+			.write_all(
+				format!("{} of {}", complete, prerequisites).as_bytes(),
+			)
+			.expect("");
+		file.write_all(b"\n\n").expect("");
+
+		// Show projected date in red if milestone will be reached late
+		if let Some(e) = node.borrow().data().scheduled_end {
+			file
+				.write_all(b"\\noindent\\textbf{Projected:} ")
+				.expect("");
+			if node.borrow().data().is_late() {
+				file.write_all(b"\\textcolor{red}{").expect("");
+				file.write_all(e.to_string().as_bytes()).expect("");
+				file.write_all(b" (late)}").expect("");
+			} else {
+				file.write_all(e.to_string().as_bytes()).expect("");
+			}
+			file.write_all(b"\n\n").expect("");
+		}
+	}
+}
+
 pub fn compile_pdf(options: &Options) {
 	// set output directory for tex file
 	let latex_args =
@@ -227,10 +493,123 @@ fn write_proofs(
 	}
 }
 
+/// URL of a Wikipedia search for a label, as written in a TeX `\href`;
+/// the label is percent-encoded, and percent signs are escaped for TeX
+pub fn wiki_search_url(label: &str) -> String {
+	let mut url =
+		"https://en.wikipedia.org/w/index.php?search=".to_string();
+	for b in label.bytes() {
+		match b {
+			b'A'..=b'Z'
+			| b'a'..=b'z'
+			| b'0'..=b'9'
+			| b'-'
+			| b'_'
+			| b'.'
+			| b'~' => url.push(b as char),
+			b' ' => url.push('+'),
+			_ => url.push_str(&format!("\\%{:02X}", b)),
+		}
+	}
+	url
+}
+
+/// Check if a node is left out because its environment is hidden in crib
+/// mode
+fn hidden_in_crib(
+	options: &Options,
+	data: &Topic,
+) -> bool {
+	options.crib
+		&& options
+			.config
+			.environments
+			.get(&data.env)
+			.is_some_and(|e| e.hide_in_crib)
+}
+
+/// Write a node in an environment defined in project configuration, or
+/// by default, followed by its proofs if the environment takes proofs
+fn write_environment(
+	options: &Options,
+	node: Rc<RefCell<Node<Topic>>>,
+	env: &Environment,
+	file: &mut File,
+) {
+	file.write_all(b"\\begin{").expect("");
+	file.write_all(env.name.as_bytes()).expect("");
+	file.write_all(b"}").expect("");
+	if env.title {
+		file.write_all(b"[").expect("");
+		file
+			.write_all(node.borrow().data().label.as_bytes())
+			.expect("");
+		file.write_all(b"]").expect("");
+	}
+	if !env.label.is_empty() {
+		file.write_all(b"\\label{").expect("");
+		file.write_all(env.label.as_bytes()).expect("");
+		file.write_all(b":").expect("");
+		file.write_all(node.borrow().path.as_bytes()).expect("");
+		file.write_all(b"}").expect("");
+	}
+	file.write_all(b"\n").expect("");
+	file
+		.write_all(node.borrow().data().main.as_bytes())
+		.expect("");
+	file.write_all(b"\\end{").expect("");
+	file.write_all(env.name.as_bytes()).expect("");
+	file.write_all(b"}\n\n").expect("");
+	if env.proofs {
+		write_proofs(options, node.clone(), file);
+	}
+}
+
+/// Print a list of related topics; topics in the document are linked to,
+/// and other topics are named in plain text
+fn print_related(
+	node: Rc<RefCell<Node<Topic>>>,
+	related: &HashMap<String, Vec<String>>,
+	targets: &HashMap<String, String>,
+	labels: &HashMap<String, String>,
+	file: &mut File,
+) {
+	let paths = match related.get(&node.borrow().path) {
+		Some(p) if !p.is_empty() => p,
+		_ => return,
+	};
+	file
+		.write_all(
+			b"\n\n\\noindent\\textbf{Related Concepts:}\\newline\n\\noindent\n",
+		)
+		.expect("");
+	file.write_all(b"\\begin{itemize}\n").expect("");
+	for path in paths.iter() {
+		let label = labels
+			.get(path)
+			.cloned()
+			.unwrap_or_else(|| label_from_path(path));
+		file.write_all(b"\\item ").expect("");
+		match targets.get(path) {
+			Some(target) => {
+				file.write_all(b"\\hyperref[").expect("");
+				file.write_all(target.as_bytes()).expect("");
+				file.write_all(b"]{").expect("");
+				file.write_all(label.as_bytes()).expect("");
+				file.write_all(b"}").expect("");
+			}
+			None => file.write_all(label.as_bytes()).expect(""),
+		}
+		file.write_all(b"\n").expect("");
+	}
+	file.write_all(b"\\end{itemize}\n").expect("");
+}
+
 /// Write text stored in nodes to tex file
 pub fn write_to_tex(
 	options: &Options,
 	sorted_nodes: &Vec<Rc<RefCell<Node<Topic>>>>,
+	background: &[Rc<RefCell<Node<Topic>>>],
 	mut final_nodes: Vec<String>,
 	max_heading_depth: usize,
 ) {
@@ -299,6 +678,26 @@ pub fn write_to_tex(
 	file.write_all(preamble.as_bytes()).expect("");
 	file.write_all(b"\n\n").expect("");
 
+	// Define environments from project configuration that give a heading
+	let mut defined: Vec<&Environment> = options
+		.config
+		.environments
+		.values()
+		.filter(|e| !e.heading.is_empty())
+		.collect();
+	defined.sort_by_key(|e| &e.name);
+	defined.dedup_by_key(|e| &e.name);
+	for e in defined.iter() {
+		file.write_all(b"\\newtheorem{").expect("");
+		file.write_all(e.name.as_bytes()).expect("");
+		file.write_all(b"}[theorem]{").expect("");
+		file.write_all(e.heading.as_bytes()).expect("");
+		file.write_all(b"}\n").expect("");
+	}
+	if !defined.is_empty() {
+		file.write_all(b"\n").expect("");
+	}
+
 	// Write title
 	if options.title.is_empty() == false {
 		file.write_all(b"\\title{").expect("");
@@ -331,11 +730,69 @@ pub fn write_to_tex(
 		file.write_all(b"\n\n\\mainmatter\n\n").expect("");
 	}
 
+	// List topics left out of the document because the reader already
+	// knows them
+	write_background(background, &mut file);
+
+	// Write Gantt chart before tasks
+	if options.gantt {
+		write_gantt(sorted_nodes, &mut file);
+	}
+
+	// Write burndown chart before tasks
+	if options.burndown {
+		let (from, to) =
+			burndown_range(sorted_nodes, options, options.as_of);
+		let weeks = compute_burndown(sorted_nodes, from, to);
+		write_burndown_chart(sorted_nodes, &weeks, &mut file);
+	}
+
 	// Write content in each node
+	let milestone_headings = milestone_headings(sorted_nodes);
+
+	// Related topics are linked to where they appear in the document;
+	// exercises are linked to where they are gathered, and hidden nodes
+	// are not linked to
+	let related = related_topics(sorted_nodes);
+	let labels: HashMap<String, String> = sorted_nodes
+		.iter()
+		.chain(background.iter())
+		.map(|n| (n.borrow().path.clone(), n.borrow().data().label.clone()))
+		.collect();
+	let targets: HashMap<String, String> = sorted_nodes
+		.iter()
+		.map(|n| n.borrow())
+		.filter(|n| match n.data().env.as_str() {
+			"ex" => options.exercises,
+			"eg" => options.examples,
+			_ => !hidden_in_crib(options, n.data()),
+		})
+		.map(|n| {
+			let prefix = if n.data().env == "ex" { "ex" } else { "rel" };
+			(n.path.clone(), format!("{}:{}", prefix, n.path))
+		})
+		.collect();
 	let mut write_appendix = false;
 	let mut first_chapter = true;
 	let mut last_chapter = false;
 
+	// Exercises are written at the end of each chapter, or at the end of
+	// the document if there are no headings
+	let chapter_depth_for_exercises =
+		chapter_heading_depth(max_heading_depth);
+	let chapter_cmd = match max_heading_depth {
+		0 => "",
+		1 | 2 => "section",
+		_ => "chapter",
+	};
+	let exercises_cmd = if chapter_cmd.is_empty() {
+		"section"
+	} else {
+		subheading_cmd(chapter_cmd)
+	};
+	let mut exercises = Exercises::default();
+	let mut appendix_started = false;
+
 	let mut prev_heading_depth_start = 0;
 	for node in &mut sorted_nodes.iter().rev() {
 		let node_path = node.borrow().path.clone();
@@ -353,9 +810,11 @@ pub fn write_to_tex(
 			// Do not insert appendix thereafter;
 			// If user suppresses appendix, don't print
 			write_appendix = options.write_appendix;
+			exercises.write_chapter(exercises_cmd, &mut file);
 			file
 				.write_all(b"\n\\appendix\n\\section{Appendix}\n\n")
 				.expect("");
+			appendix_started = true;
 		}
 
 		// Select heading style and heading label prefixes for headings
@@ -429,6 +888,15 @@ pub fn write_to_tex(
 		for ht in node.borrow().data().heading_titles.clone() {
 			prev_heading_depth_start =
 				node.borrow().data().heading_depth_start;
+			// Exercises end the chapter before the heading of a new one
+			if !ht.is_empty() && i <= chapter_depth_for_exercises {
+				exercises.write_chapter(exercises_cmd, &mut file);
+				if i == chapter_depth_for_exercises {
+					exercises.start_chapter(&ht);
+				} else {
+					exercises.start_chapter("");
+				}
+			}
 			if i <= max_heading_depth {
 				if ht.is_empty() == false {
 					// Make chapters refsections so that bibliography is printed
@@ -481,6 +949,43 @@ pub fn write_to_tex(
 			i += 1;
 		}
 
+		// Start a section for each milestone before the first of the nodes
+		// leading up to it
+		if let Some(ms) = milestone_headings.get(&node_path) {
+			file.write_all(b"\\section{").expect("");
+			file
+				.write_all(ms.borrow().data().label.as_bytes())
+				.expect("");
+			file.write_all(b"}\\label{ms:").expect("");
+			file.write_all(ms.borrow().path.as_bytes()).expect("");
+			file.write_all(b"}\n\n").expect("");
+		}
+
+		// Exercises are gathered and written at the end of the chapter
+		if node.borrow().data().env == "ex" {
+			if options.exercises {
+				exercises.add(node.clone());
+			}
+			continue;
+		}
+
+		// Examples are hidden unless requested
+		if node.borrow().data().env == "eg" && !options.examples {
+			continue;
+		}
+
+		// Environments hidden in crib mode are left out entirely
+		if hidden_in_crib(options, node.borrow().data()) {
+			continue;
+		}
+
+		// Anchor for links from related topics
+		if related.contains_key(&node_path) {
+			file.write_all(b"\\phantomsection\\label{rel:").expect("");
+			file.write_all(node_path.as_bytes()).expect("");
+			file.write_all(b"}\n").expect("");
+		}
+
 		// Write source YAML file name
 		if options.yaml == true {
 			file
@@ -514,13 +1019,11 @@ pub fn write_to_tex(
 				file.write_all(b"}\n").expect("");
 
 				// Show task status
-				file
-					.write_all(b"\\marginpar{\\ding{51} \\textbf{DONE}}\n")
-					.expect("");
-				file.write_all(b"\\reversemarginpar\n\n").expect("");
+				print_status(node.clone(), &mut file);
 
 				// Print deadline, start, and end dates
-				print_deadline(node.clone(), &mut file);
+				print_deadline(node.clone(), options.as_of, &mut file);
+				print_repeat(node.clone(), &mut file);
 				print_assignees(node.clone(), &mut file);
 				print_start_end_dates(node.clone(), &mut file);
 			}
@@ -537,36 +1040,50 @@ pub fn write_to_tex(
 				file.write_all(b"}\n").expect("");
 
 				// Show task status
-				if node.borrow().data().complete.is_some() {
-					file
-						.write_all(b"\\marginpar{\\ding{51} \\textbf{DONE}}\n")
-						.expect("");
-				} else {
-					file
-						.write_all(b"\\marginpar{$\\square$  \\textbf{TO DO}}\n")
-						.expect("");
-				}
-				file.write_all(b"\\reversemarginpar\n\n").expect("");
+				print_status(node.clone(), &mut file);
 
-				// Display expected duration for task if incomplete
-				if node.borrow().data().complete.is_none() {
-					if node.borrow().data().expected > 0 {
+				// Display expected duration for task if still open
+				if node.borrow().data().is_open() {
+					if node.borrow().data().expected > 0.0 {
 						file
 							.write_all(b"\\noindent\\textbf{Expected Duration:} ")
 							.expect("");
 						file
 							.write_all(
-								node.borrow().data().expected.to_string().as_bytes(),
+								format_days(node.borrow().data().expected).as_bytes(),
 							)
 							.expect("");
-						file.write_all(b" days\n\n").expect("");
+						file.write_all(b" days").expect("");
+						print_three_point_estimate(node.clone(), &mut file);
+						file.write_all(b"\n\n").expect("");
 					}
 				}
 
 				// Print deadline, start, and end dates
-				print_deadline(node.clone(), &mut file);
+				print_deadline(node.clone(), options.as_of, &mut file);
+				print_repeat(node.clone(), &mut file);
 				print_assignees(node.clone(), &mut file);
 				print_start_end_dates(node.clone(), &mut file);
+				print_progress(node.clone(), &mut file);
+				print_projected_dates(node.clone(), &mut file);
+				print_forecast(node.clone(), &mut file);
+			}
+
+			// Milestone; reached once its prerequisites are complete
+			"ms" => {
+				file
+					.write_all(
+						b"\n\\vspace{5mm}\n\\noindent\n\\textbf{Milestone: ",
+					)
+					.expect("");
+				file
+					.write_all(node.borrow().data().label.as_bytes())
+					.expect("");
+				file.write_all(b"}\n").expect("");
+
+				print_status(node.clone(), &mut file);
+				print_deadline(node.clone(), options.as_of, &mut file);
+				print_milestone(node.clone(), &mut file);
 			}
 			_ => (),
 		}
@@ -605,6 +1122,12 @@ pub fn write_to_tex(
 					.write_all(node.borrow().data().main.as_bytes())
 					.expect("");
 			}
+			// Milestone
+			"ms" => {
+				file
+					.write_all(node.borrow().data().main.as_bytes())
+					.expect("");
+			}
 			// Motivation
 			"mot" => (),
 			// Abstract
@@ -617,123 +1140,6 @@ pub fn write_to_tex(
 					file.write_all(b"\\end{abstract}\n\n").expect("");
 				}
 			}
-			// Definition
-			"def" => {
-				file.write_all(b"\\begin{definition}[").expect("");
-				file
-					.write_all(node.borrow().data().label.as_bytes())
-					.expect("");
-				file.write_all(b"]\\label{def:").expect("");
-				file.write_all(node.borrow().path.as_bytes()).expect("");
-				file.write_all(b"}\n").expect("");
-				file
-					.write_all(node.borrow().data().main.as_bytes())
-					.expect("");
-				file.write_all(b"\\end{definition}\n\n").expect("");
-			}
-			// Example
-			"eg" => {
-				if options.examples == true {
-					file.write_all(b"\\begin{example}[").expect("");
-					file
-						.write_all(node.borrow().data().label.as_bytes())
-						.expect("");
-					file.write_all(b"]\\label{eg:").expect("");
-					file.write_all(node.borrow().path.as_bytes()).expect("");
-					file.write_all(b"}\n").expect("");
-					file
-						.write_all(node.borrow().data().main.as_bytes())
-						.expect("");
-					file.write_all(b"\\end{example}\n\n").expect("");
-				}
-			}
-			// Lemma
-			"lem" => {
-				file.write_all(b"\\begin{lemma}[").expect("");
-				file
-					.write_all(node.borrow().data().label.as_bytes())
-					.expect("");
-				file.write_all(b"]\\label{lem:").expect("");
-				file.write_all(node.borrow().path.as_bytes()).expect("");
-				file.write_all(b"}\n").expect("");
-				file
-					.write_all(node.borrow().data().main.as_bytes())
-					.expect("");
-				file.write_all(b"\\end{lemma}\n\n").expect("");
-				write_proofs(&options, node.clone(), &mut file);
-			}
-			// Theorem
-			"thm" => {
-				file.write_all(b"\\begin{theorem}[").expect("");
-				file
-					.write_all(node.borrow().data().label.as_bytes())
-					.expect("");
-				file.write_all(b"]\\label{thm:").expect("");
-				file.write_all(node.borrow().path.as_bytes()).expect("");
-				file.write_all(b"}\n").expect("");
-				file
-					.write_all(node.borrow().data().main.as_bytes())
-					.expect("");
-				file.write_all(b"\\end{theorem}\n\n").expect("");
-				write_proofs(&options, node.clone(), &mut file);
-			}
-			// Corollary
-			"cor" => {
-				file.write_all(b"\\begin{corollary}[").expect("");
-				file
-					.write_all(node.borrow().data().label.as_bytes())
-					.expect("");
-				file.write_all(b"]\\label{cor:").expect("");
-				file.write_all(node.borrow().path.as_bytes()).expect("");
-				file.write_all(b"}\n").expect("");
-				file
-					.write_all(node.borrow().data().main.as_bytes())
-					.expect("");
-				file.write_all(b"\\end{corollary}\n\n").expect("");
-				write_proofs(&options, node.clone(), &mut file);
-			}
-			// Rule
-			"rule" => {
-				file.write_all(b"\\begin{rule}[").expect("");
-				file
-					.write_all(node.borrow().data().label.as_bytes())
-					.expect("");
-				file.write_all(b"]\\label{rule:").expect("");
-				file.write_all(node.borrow().path.as_bytes()).expect("");
-				file.write_all(b"}\n").expect("");
-				file
-					.write_all(node.borrow().data().main.as_bytes())
-					.expect("");
-				file.write_all(b"\\end{rule}\n\n").expect("");
-				write_proofs(&options, node.clone(), &mut file);
-			}
-			// Fact
-			"fact" => {
-				file.write_all(b"\\begin{fact}[").expect("");
-				file
-					.write_all(node.borrow().data().label.as_bytes())
-					.expect("");
-				file.write_all(b"]\\label{fact:").expect("");
-				file.write_all(node.borrow().path.as_bytes()).expect("");
-				file.write_all(b"}\n").expect("");
-				file
-					.write_all(node.borrow().data().main.as_bytes())
-					.expect("");
-				file.write_all(b"\\end{fact}\n\n").expect("");
-				write_proofs(&options, node.clone(), &mut file);
-			}
-			// Remark
-			"rem" => {
-				file.write_all(b"\\begin{remark}[").expect("");
-				file
-					.write_all(node.borrow().data().label.as_bytes())
-					.expect("");
-				file.write_all(b"]").expect("");
-				file
-					.write_all(node.borrow().data().main.as_bytes())
-					.expect("");
-				file.write_all(b"\\end{remark}\n\n").expect("");
-			}
 			// Algorithm
 			"alg" => (),
 			// Code listing from text
@@ -807,7 +1213,18 @@ pub fn write_to_tex(
 					.write_all(node.borrow().data().main.as_bytes())
 					.expect("");
 			}
-			_ => panic!("Invalid environment specified for node"),
+			// Environments defined in project configuration, or by default
+			env => match options.config.environments.get(env) {
+				Some(e) => {
+					write_environment(&options, node.clone(), e, &mut file)
+				}
+				None => panic!(
+					"Invalid environment `{}` specified for {}; define it under \
+					 `environments` in ../tok.yml",
+					env,
+					node.borrow().path
+				),
+			},
 		}
 
 		// Print alternate names
@@ -826,23 +1243,30 @@ pub fn write_to_tex(
 			file.write_all(b"\\end{itemize}\n").expect("");
 		}
 
+		// Print related topics
+		print_related(node.clone(), &related, &targets, &labels, &mut file);
+
 		// Link to Wikipedia
 		if (options.crib == false)
 			& (options.show_wiki == true)
 			& (node.borrow().data().env != "x")
 			& (node.borrow().data().env != "task")
 			& (node.borrow().data().env != "done")
+			& (node.borrow().data().env != "ms")
+			& (options
+				.config
+				.environments
+				.get(&node.borrow().data().env)
+				.is_none_or(|e| e.wiki))
 			& (node.borrow().data().nowiki == false)
 		{
 			file.write_all(b"\n\n\\noindent\n").expect("");
 			file.write_all(b"\\href{").expect("");
 			if node.borrow().data().wiki.is_empty() == true {
-				let wiki_search_url: String =
-					"https://en.wikipedia.org/w/index.php?search=".to_string();
-				let wiki_search_term: String =
-					node.borrow().data().label.clone();
 				file
-					.write_all((wiki_search_url + &wiki_search_term).as_bytes())
+					.write_all(
+						wiki_search_url(&node.borrow().data().label).as_bytes(),
+					)
 					.expect("");
 			} else if options.crib == false {
 				file
@@ -905,6 +1329,21 @@ pub fn write_to_tex(
 		file.write_all(b"\n\n").expect("");
 	}
 
+	// Write exercises in the last chapter, and solutions to all exercises
+	// in an appendix
+	exercises.write_chapter(exercises_cmd, &mut file);
+	let solutions_cmd = if chapter_cmd.is_empty() {
+		"section"
+	} else {
+		chapter_cmd
+	};
+	exercises.write_solutions(
+		solutions_cmd,
+		subheading_cmd(solutions_cmd),
+		!appendix_started,
+		&mut file,
+	);
+
 	// Write backmatter
 	if backmatter.is_empty() == false {
 		file.write_all(b"\n\n\\backmatter\n\n").expect("");
@@ -1,9 +1,62 @@
+use crate::calendar::parse_duration;
+use crate::date::{parse_date, Repeat};
 use crate::node::{compare_dag_cost, Node};
+use crate::options::Options;
 use crate::yaml::DeserializedMap;
-use chrono::offset::{TimeZone, Utc};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use serde_yaml::Value;
 use std::{cell::RefCell, cmp::Ordering, collections::HashMap, rc::Rc};
 use titlecase::titlecase;
 
+/// Status of a task
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Status {
+	Todo,
+	InProgress,
+	Blocked,
+	Waiting,
+	Cancelled,
+	Done,
+}
+
+impl Status {
+	/// Parse status from value of `status` key; case, spaces, hyphens,
+	/// and underscores are ignored
+	pub fn parse(s: &str) -> Result<Status, String> {
+		match s
+			.to_lowercase()
+			.replace(" ", "")
+			.replace("-", "")
+			.replace("_", "")
+			.as_str()
+		{
+			"todo" => Ok(Status::Todo),
+			"inprogress" => Ok(Status::InProgress),
+			"blocked" => Ok(Status::Blocked),
+			"waiting" => Ok(Status::Waiting),
+			"cancelled" | "canceled" => Ok(Status::Cancelled),
+			"done" => Ok(Status::Done),
+			_ => Err(format!(
+				"`{}` is not one of todo, in-progress, blocked, waiting, \
+				 cancelled, done",
+				s
+			)),
+		}
+	}
+
+	/// Name of status as it should appear in document
+	pub fn name(&self) -> &'static str {
+		match self {
+			Status::Todo => "TO DO",
+			Status::InProgress => "IN PROGRESS",
+			Status::Blocked => "BLOCKED",
+			Status::Waiting => "WAITING",
+			Status::Cancelled => "CANCELLED",
+			Status::Done => "DONE",
+		}
+	}
+}
+
 pub struct Topic {
 	/// Name as it should appear in document
 	pub label: String,
@@ -36,10 +89,16 @@ pub struct Topic {
 	/// for theorem environments only);
 	/// input as sequence of multiline strings in YAML file
 	pub pfs: Vec<String>,
-	// example of other items that will not be included as successors
-	// pub example_of: Vec<String>,
-	// examples of this item to include
-	// pub example_paths: Vec<String>,
+	/// Solution to an exercise (`ex` nodes only); placed in an appendix
+	pub sol: String,
+	/// Topics this example illustrates (`eg` nodes only); the example is
+	/// placed directly after the first of them in the document
+	pub eg_of: Vec<String>,
+	/// Examples of this topic, placed directly after it in the document
+	pub examples: Vec<String>,
+	/// Related topics, with no dependency relationship in either
+	/// direction; relationships are symmetric
+	pub rel: Vec<String>,
 	/// Link to Wikipedia page; if empty, will result in a link to a
 	/// Wikipedia search query for this node's label; if not empty,
 	/// author-provided link will be used instead
@@ -54,19 +113,34 @@ pub struct Topic {
 	/// Questions for author to answer in subsequent drafts
 	pub q: Vec<String>,
 	/// Start date for a task
-	pub start: Option<Vec<usize>>,
+	pub start: Option<NaiveDate>,
+	/// Start time for a task, if given with start date
+	pub start_time: Option<NaiveTime>,
 	/// Completion date for a task
-	pub complete: Option<Vec<usize>>,
+	pub complete: Option<NaiveDate>,
+	/// Completion time for a task, if given with completion date
+	pub complete_time: Option<NaiveTime>,
 	/// Task deadline
-	pub deadline: Option<Vec<usize>>,
-	/// Expected duration of a task (in days)
-	pub expected: usize,
-	/// Actual duration of a task (in days)
-	pub duration: usize,
-	// we allow different types of predecessors
-	// so that we can talk about relationships between items
-	pub gen: Vec<String>,
-	pub case: Vec<String>,
+	pub deadline: Option<NaiveDate>,
+	/// Time of day a task is due, if given with deadline
+	pub deadline_time: Option<NaiveTime>,
+	/// Interval between occurrences of a repeating task; the deadline of
+	/// a repeating task is the due date of its next open occurrence
+	pub repeat: Option<Repeat>,
+	/// Last possible due date of an occurrence of a repeating task
+	pub until: Option<NaiveDate>,
+	/// Due dates of occurrences of a repeating task, up to and including
+	/// the next open occurrence, and whether each has been completed
+	pub occurrences: Vec<(NaiveDate, bool)>,
+	/// Expected duration of a task (in working days)
+	pub expected: f64,
+	/// Actual duration of a task (in working days)
+	pub duration: f64,
+	/// Three-point estimate of the duration of a task (in working days);
+	/// either all or none are given
+	pub optimistic: Option<f64>,
+	pub likely: Option<f64>,
+	pub pessimistic: Option<f64>,
 	/// List of references that will be added to .bib file for this node
 	pub src: Vec<String>,
 	pub heading_depth: usize,
@@ -76,6 +150,39 @@ pub struct Topic {
 	pub heading_titles: Vec<String>,
 	/// Assignee(s) for tasks
 	pub assgn: Vec<String>,
+	/// Status of a task; if not given, inferred from `env` and from start
+	/// and completion dates
+	pub status: Status,
+	/// Percentage of a task that is complete
+	pub progress: f64,
+	/// Dated notes on the progress of a task, in chronological order
+	pub log: Vec<(NaiveDate, Option<NaiveTime>, String)>,
+	/// Sum of expected durations of tasks in the subgraph ending at this
+	/// node (in working days)
+	pub rollup_expected: f64,
+	/// Remaining work in tasks in the subgraph ending at this node (in
+	/// working days)
+	pub rollup_remaining: f64,
+	/// Number of complete prerequisites of a milestone, and number of
+	/// prerequisites; set by `derive_milestone_status`
+	pub prerequisites_complete: (usize, usize),
+	/// Latest deadline among (direct or indirect) prerequisites; only set
+	/// if later than this task's deadline
+	pub req_deadline: Option<NaiveDate>,
+	/// Earliest date this task can be completed given the expected
+	/// durations of incomplete prerequisites; only set if later than this
+	/// task's deadline
+	pub earliest_finish: Option<NaiveDate>,
+	/// Projected start date of an incomplete task
+	pub scheduled_start: Option<NaiveDate>,
+	/// Projected completion date of an incomplete task
+	pub scheduled_end: Option<NaiveDate>,
+	/// Probability of meeting deadline, from Monte Carlo forecast
+	pub deadline_probability: Option<f64>,
+	/// Dates by which an incomplete task is finished in 50 and 90 percent
+	/// of simulated outcomes
+	pub forecast_p50: Option<NaiveDate>,
+	pub forecast_p90: Option<NaiveDate>,
 }
 
 impl Topic {
@@ -92,33 +199,173 @@ impl Topic {
 			lines: vec![],
 			post: String::from(""),
 			pfs: vec![],
-			// example_of: vec![],
-			// example_paths: vec![],
+			sol: String::from(""),
+			eg_of: vec![],
+			examples: vec![],
+			rel: vec![],
 			wiki: String::from(""),
 			nowiki: false,
 			urls: HashMap::new(),
 			q: vec![],
 			start: None,
+			start_time: None,
 			complete: None,
+			complete_time: None,
 			deadline: None,
-			duration: 0,
-			expected: 0,
-			gen: vec![],
-			case: vec![],
+			deadline_time: None,
+			repeat: None,
+			until: None,
+			occurrences: vec![],
+			duration: 0.0,
+			expected: 0.0,
+			optimistic: None,
+			likely: None,
+			pessimistic: None,
 			src: vec![],
 			heading_depth: 0,
 			heading_depth_start: 0,
 			heading_titles: vec![],
 			assgn: vec![],
+			status: Status::Todo,
+			progress: 0.0,
+			log: vec![],
+			rollup_expected: 0.0,
+			rollup_remaining: 0.0,
+			prerequisites_complete: (0, 0),
+			req_deadline: None,
+			earliest_finish: None,
+			scheduled_start: None,
+			scheduled_end: None,
+			deadline_probability: None,
+			forecast_p50: None,
+			forecast_p90: None,
+		}
+	}
+
+	/// Check if this node is a task
+	pub fn is_task(&self) -> bool {
+		self.env == "task" || self.env == "done"
+	}
+
+	/// Check if this node is a milestone
+	pub fn is_milestone(&self) -> bool {
+		self.env == "ms"
+	}
+
+	/// Check if this node is a task that has been completed, or a
+	/// milestone that has been reached
+	pub fn is_complete(&self) -> bool {
+		(self.is_task() || self.is_milestone())
+			&& self.status == Status::Done
+	}
+
+	/// Check if this node is a task that still requires work; i.e. not
+	/// completed or cancelled
+	pub fn is_open(&self) -> bool {
+		self.is_task()
+			&& self.status != Status::Done
+			&& self.status != Status::Cancelled
+	}
+
+	/// Check if this node is an open task or a milestone that has not been
+	/// reached, i.e. a node whose deadline has yet to be met
+	pub fn is_pending(&self) -> bool {
+		self.is_open() || (self.is_milestone() && !self.is_complete())
+	}
+
+	/// Number of days by which a pending task or milestone is past its
+	/// deadline on a given day, if any
+	pub fn overdue_days(
+		&self,
+		today: NaiveDate,
+	) -> Option<i64> {
+		let dl = self.deadline?;
+		if self.is_pending() && dl < today {
+			Some((today - dl).num_days())
+		} else {
+			None
+		}
+	}
+
+	/// Remaining work in a task (in working days), i.e. the part of its
+	/// expected duration that is not yet complete; zero unless the task
+	/// is open
+	pub fn remaining(&self) -> f64 {
+		if self.is_open() {
+			self.expected * (1.0 - self.progress / 100.0)
+		} else {
+			0.0
+		}
+	}
+
+	/// Check if task should appear after other tasks, regardless of
+	/// deadlines or cost
+	pub fn sinks(&self) -> bool {
+		self.is_task()
+			&& (self.status == Status::Blocked
+				|| self.status == Status::Cancelled)
+	}
+
+	/// Check if deadline is earlier than the deadline of a prerequisite
+	pub fn deadline_is_inverted(&self) -> bool {
+		self.req_deadline.is_some()
+	}
+
+	/// Check if deadline cannot be met given the expected durations of
+	/// incomplete prerequisites
+	pub fn deadline_is_infeasible(&self) -> bool {
+		self.earliest_finish.is_some()
+	}
+
+	/// Check if task is projected to finish after its deadline
+	pub fn is_late(&self) -> bool {
+		match (self.scheduled_end, self.deadline) {
+			(Some(e), Some(dl)) => e > dl,
+			_ => false,
 		}
 	}
+
+	/// Date and time a task is due; a deadline without a time of day is
+	/// due at the end of the day
+	pub fn deadline_datetime(&self) -> Option<NaiveDateTime> {
+		self.deadline.map(|d| {
+			d.and_time(self.deadline_time.unwrap_or_else(|| {
+				NaiveTime::from_hms_opt(23, 59, 59).unwrap()
+			}))
+		})
+	}
+}
+
+/// Read a path, or a list of paths, given for `key`
+fn path_list(
+	v: Value,
+	key: &str,
+	filename: &str,
+) -> Vec<String> {
+	match v {
+		Value::String(s) => vec![s],
+		_ => serde_yaml::from_value(v).unwrap_or_else(|_| {
+			panic!(
+				"Invalid {} in {}: expected a list of files",
+				key, filename
+			)
+		}),
+	}
 }
 
 /// Create a Topic and fill data members based on key/value pairs
 pub fn create_topic(
 	filename: &String,
 	yaml_content: DeserializedMap,
+	options: &Options,
 ) -> Rc<RefCell<Node<Topic>>> {
+	let calendar = &options.config.calendar;
+	let duration = |v: &Value, key: &str| -> f64 {
+		parse_duration(v, calendar.hours_per_day).unwrap_or_else(|e| {
+			panic!("Invalid {} duration in {}: {}", key, filename, e)
+		})
+	};
+
 	// store content in node
 	let node = Node::new(&filename, Topic::new());
 	let mut data = Topic::new();
@@ -143,6 +390,9 @@ pub fn create_topic(
 		titlecase(&label[0..file_extension_start].to_string()[..])
 	};
 
+	let mut status: Option<Status> = None;
+	let mut done_on: Vec<NaiveDate> = vec![];
+	let mut progress: Option<f64> = None;
 	for (k, v) in yaml_content.pairs {
 		match k.as_ref() {
 			"req" => {
@@ -172,42 +422,254 @@ pub fn create_topic(
 			"urls" => data.urls = serde_yaml::from_value(v).expect(""),
 			"q" => data.q = serde_yaml::from_value(v).expect(""),
 			"pfs" => data.pfs = serde_yaml::from_value(v).expect(""),
+			"sol" => data.sol = serde_yaml::from_value(v).expect(""),
 			"lines" => data.lines = serde_yaml::from_value(v).expect(""),
-			"start" => data.start = serde_yaml::from_value(v).expect(""),
-			"expected" => {
-				data.expected = serde_yaml::from_value(v).expect("")
+			"start" => {
+				let (d, t) = parse_date(&v).unwrap_or_else(|e| {
+					panic!("Invalid start date in {}: {}", filename, e)
+				});
+				data.start = Some(d);
+				data.start_time = t;
+			}
+			"expected" => data.expected = duration(&v, "expected"),
+			"optimistic" => {
+				data.optimistic = Some(duration(&v, "optimistic"))
+			}
+			"likely" => data.likely = Some(duration(&v, "likely")),
+			"pessimistic" => {
+				data.pessimistic = Some(duration(&v, "pessimistic"))
 			}
 			"complete" => {
-				data.complete = serde_yaml::from_value(v).expect("")
+				let (d, t) = parse_date(&v).unwrap_or_else(|e| {
+					panic!("Invalid completion date in {}: {}", filename, e)
+				});
+				data.complete = Some(d);
+				data.complete_time = t;
+			}
+			// Milestones may give a date instead of a deadline
+			"deadline" | "date" => {
+				let (d, t) = parse_date(&v).unwrap_or_else(|e| {
+					panic!("Invalid deadline in {}: {}", filename, e)
+				});
+				data.deadline = Some(d);
+				data.deadline_time = t;
+			}
+			"repeat" => {
+				let s: String = serde_yaml::from_value(v).expect("");
+				data.repeat = Some(Repeat::parse(&s).unwrap_or_else(|e| {
+					panic!("Invalid repeat in {}: {}", filename, e)
+				}));
+			}
+			"until" => {
+				let (d, _) = parse_date(&v).unwrap_or_else(|e| {
+					panic!("Invalid until date in {}: {}", filename, e)
+				});
+				data.until = Some(d);
+			}
+			"done_on" => {
+				let dates: Vec<Value> = serde_yaml::from_value(v)
+					.unwrap_or_else(|_| {
+						panic!("Invalid done_on in {}: expected a list", filename)
+					});
+				for d in dates.iter() {
+					done_on.push(
+						parse_date(d)
+							.unwrap_or_else(|e| {
+								panic!("Invalid done_on date in {}: {}", filename, e)
+							})
+							.0,
+					);
+				}
 			}
-			"deadline" => {
-				data.deadline = serde_yaml::from_value(v).expect("")
+			"progress" => {
+				let p = match &v {
+					Value::String(s) => {
+						s.trim().trim_end_matches('%').trim().parse().ok()
+					}
+					_ => v.as_f64(),
+				};
+				progress =
+					Some(p.filter(|p| (0.0..=100.0).contains(p)).unwrap_or_else(
+						|| {
+							panic!(
+						"Invalid progress in {}: expected a percentage from 0 to 100",
+						filename
+					)
+						},
+					));
 			}
-			"gen" => data.gen = serde_yaml::from_value(v).expect(""),
-			"case" => data.case = serde_yaml::from_value(v).expect(""),
+			"log" => {
+				let entries: Vec<HashMap<String, Value>> = serde_yaml::from_value(v)
+					.unwrap_or_else(|_| {
+						panic!(
+							"Invalid log in {}: expected a list of entries with date and note",
+							filename
+						)
+					});
+				for e in entries.iter() {
+					let (d, t) = e
+						.get("date")
+						.ok_or_else(|| "missing date".to_string())
+						.and_then(parse_date)
+						.unwrap_or_else(|e| {
+							panic!("Invalid log entry in {}: {}", filename, e)
+						});
+					let note = e
+						.get("note")
+						.and_then(|n| n.as_str())
+						.unwrap_or("")
+						.to_string();
+					data.log.push((d, t, note));
+				}
+				data.log.sort_by_key(|x| (x.0, x.1));
+			}
+			"eg_of" => data.eg_of = path_list(v, "eg_of", filename),
+			"examples" => data.examples = path_list(v, "examples", filename),
+			"rel" => data.rel = path_list(v, "rel", filename),
+			"gen" => node.borrow_mut().gen = path_list(v, "gen", filename),
+			"case" => node.borrow_mut().case = path_list(v, "case", filename),
 			"src" => data.src = serde_yaml::from_value(v).expect(""),
 			"assgn" => data.assgn = serde_yaml::from_value(v).expect(""),
+			"status" => {
+				let s: String = serde_yaml::from_value(v).expect("");
+				status = Some(Status::parse(&s).unwrap_or_else(|e| {
+					panic!("Invalid status in {}: {}", filename, e)
+				}));
+			}
 			_ => (),
 		}
 	}
 
+	// An example requires the topics it illustrates, and a topic includes
+	// its examples
+	node.borrow_mut().req.extend(data.eg_of.iter().cloned());
+	node.borrow_mut().incl.extend(data.examples.iter().cloned());
+
+	// Expand a repeating task into occurrences; each occurrence is due a
+	// fixed interval after the previous one, and is complete if its due
+	// date is listed under `done_on`
+	if let Some(repeat) = data.repeat {
+		let first = data.deadline.unwrap_or_else(|| {
+			panic!(
+				"Repeating task {} requires a deadline for its first occurrence",
+				filename
+			)
+		});
+		let last_completed = done_on.iter().max().cloned();
+		let mut n = 0;
+		loop {
+			let due = repeat.nth(first, n);
+			if data.until.is_some_and(|u| due > u) {
+				break;
+			}
+			let open_found = data.occurrences.iter().any(|x| !x.1);
+			if open_found && last_completed.is_none_or(|c| due > c) {
+				break;
+			}
+			data.occurrences.push((due, done_on.contains(&due)));
+			n += 1;
+		}
+		if data.occurrences.is_empty() {
+			panic!(
+				"Repeating task {} ends before its first occurrence",
+				filename
+			);
+		}
+		for c in done_on.iter() {
+			if !data.occurrences.iter().any(|x| x.0 == *c) {
+				panic!("{} is not an occurrence of {}", c, filename);
+			}
+		}
+
+		// Only the next open occurrence is shown; once all occurrences are
+		// complete, so is the task
+		match data.occurrences.iter().find(|x| !x.1) {
+			Some(next) => data.deadline = Some(next.0),
+			None => {
+				let last = data.occurrences.last().map(|x| x.0);
+				data.deadline = last;
+				if data.complete.is_none() {
+					data.complete = last;
+				}
+			}
+		}
+	}
+
+	// Infer task status if not given
+	data.status = match status {
+		Some(s) => s,
+		None => {
+			if data.env == "done" || data.complete.is_some() {
+				Status::Done
+			} else if data.start.is_some()
+				|| progress.is_some_and(|p| p > 0.0)
+			{
+				Status::InProgress
+			} else {
+				Status::Todo
+			}
+		}
+	};
+
+	// Check three-point estimate; use its mean as the expected duration
+	// if none is given
+	match (data.optimistic, data.likely, data.pessimistic) {
+		(None, None, None) => (),
+		(Some(o), Some(m), Some(p)) => {
+			if o < 0.0 || o > m || m > p {
+				panic!(
+					"Three-point estimate in {} must satisfy 0 <= optimistic <= \
+					 likely <= pessimistic",
+					filename
+				)
+			}
+			if data.expected == 0.0 {
+				data.expected = (o + 4.0 * m + p) / 6.0;
+			}
+		}
+		_ => panic!(
+			"Three-point estimate in {} requires optimistic, likely, and \
+			 pessimistic",
+			filename
+		),
+	}
+
+	// Milestones mark a point in time and take no time themselves
+	if data.is_milestone() {
+		data.expected = 0.0;
+		data.optimistic = None;
+		data.likely = None;
+		data.pessimistic = None;
+	}
+
+	// Completed tasks are entirely complete
+	data.progress = if data.status == Status::Done {
+		100.0
+	} else {
+		progress.unwrap_or(0.0)
+	};
+
 	// Update node cost
-	if data.env == "task" {
-		if data.start.is_some() && data.complete.is_some() {
+	if data.env == "task" || data.env == "done" {
+		if let (Some(a), Some(b)) = (data.start, data.complete) {
 			// Start and completion dates known; compute duration
-			let s = data.start.clone().unwrap();
-			let c = data.complete.clone().unwrap();
-			let a = Utc.ymd(s[0] as i32, s[1] as u32, s[2] as u32);
-			let b = Utc.ymd(c[0] as i32, c[1] as u32, c[2] as u32);
 			if b < a {
-				panic!("Completion date must not be before start date.")
+				panic!(
+					"Completion date {} must not be before start date {} in {}",
+					b, a, filename
+				)
 			} else {
-				data.duration = (b - a).num_days() as usize;
+				// Only working time counts towards duration
+				data.duration = calendar.working_time_between(
+					(a, data.start_time),
+					(b, data.complete_time),
+					&data.assgn,
+				);
 			}
-			node.borrow_mut().cost = 1 + data.duration;
+			node.borrow_mut().cost = 1 + data.duration.ceil() as usize;
 		} else {
 			// Start date or completion date missing; only know expected duration
-			node.borrow_mut().cost = 1 + data.expected;
+			node.borrow_mut().cost = 1 + data.expected.ceil() as usize;
 		}
 	} else {
 		// Not a task; use amount of text as a heuristic for computing cost
@@ -240,6 +702,13 @@ pub fn compute_ordering(
 	a: &Rc<RefCell<Node<Topic>>>,
 	b: &Rc<RefCell<Node<Topic>>>,
 ) -> Ordering {
+	// Blocked and cancelled tasks appear after other nodes
+	let a_sinks = a.borrow().data().sinks();
+	let b_sinks = b.borrow().data().sinks();
+	if a_sinks != b_sinks {
+		return a_sinks.cmp(&b_sinks);
+	}
+
 	if a.borrow().data().deadline.is_none()
 		&& b.borrow().data().deadline.is_none()
 	{
@@ -255,31 +724,9 @@ pub fn compute_ordering(
 		Ordering::Greater
 	} else {
 		// both nodes have deadlines
-		// compare deadlines, starting with years
-		let adl = a.borrow().data().deadline.clone().unwrap();
-		let bdl = b.borrow().data().deadline.clone().unwrap();
-
-		// compare years
-		if adl[0] > bdl[0] {
-			Ordering::Greater
-		} else if adl[0] < bdl[0] {
-			Ordering::Less
-		} else {
-			// same year, compare months
-			if adl[1] > bdl[1] {
-				Ordering::Greater
-			} else if adl[1] < bdl[1] {
-				Ordering::Less
-			} else {
-				// same month, compare dates
-				if adl[2] > bdl[2] {
-					Ordering::Greater
-				} else if adl[2] < bdl[2] {
-					Ordering::Less
-				} else {
-					Ordering::Equal
-				}
-			}
-		}
+		a.borrow()
+			.data()
+			.deadline_datetime()
+			.cmp(&b.borrow().data().deadline_datetime())
 	}
 }